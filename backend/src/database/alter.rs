@@ -0,0 +1,177 @@
+use frontend::{AlterTableAction, TableDefinition};
+
+use crate::{
+    errors::{BEErrors, BEResult},
+    vm::expression::evaluate_default,
+};
+
+use super::page::MAX_SCHEMA_VERSION;
+
+/// Returns `definition` with a column change applied.
+/// Adding or dropping a column changes the record layout, so the current layout is kept
+/// in the schema history for rows written before the change.
+pub(super) fn alter_columns(
+    definition: &TableDefinition,
+    action: AlterTableAction,
+    has_rows: bool,
+) -> BEResult<TableDefinition> {
+    let mut definition = definition.clone();
+    let position = |definition: &TableDefinition, name: &str| {
+        definition
+            .columns
+            .iter()
+            .position(|it| it.0 == name)
+            .ok_or_else(|| BEErrors::MissingColumn(format!("Column {name} not found")))
+    };
+    let ensure_unique = |definition: &TableDefinition, name: &str| {
+        if definition.columns.iter().any(|it| it.0 == name) {
+            return Err(BEErrors::InvalidSchemaChange(format!(
+                "column {name} already present in table"
+            )));
+        }
+        Ok(())
+    };
+
+    match action {
+        AlterTableAction::AddColumn(column) => {
+            ensure_unique(&definition, &column.0)?;
+            match evaluate_default(&column)? {
+                Some(_) => {}
+                None if has_rows => {
+                    return Err(BEErrors::InvalidSchemaChange(format!(
+                        "column {} needs a default as the table has rows",
+                        column.0
+                    )))
+                }
+                None => {}
+            }
+            push_current_layout(&mut definition)?;
+            definition.columns.push(column);
+        }
+        AlterTableAction::DropColumn(name) => {
+            let dropped = position(&definition, &name.0)?;
+            if definition.columns.len() == 1 {
+                return Err(BEErrors::InvalidSchemaChange(format!(
+                    "can not drop {}, the only column of the table",
+                    name.0
+                )));
+            }
+            push_current_layout(&mut definition)?;
+            definition.columns.remove(dropped);
+            for (_, index) in definition
+                .schema_history
+                .iter_mut()
+                .flat_map(|it| it.0.iter_mut())
+            {
+                *index = match *index {
+                    Some(index) if index == dropped => None,
+                    Some(index) if index > dropped => Some(index - 1),
+                    index => index,
+                };
+            }
+        }
+        AlterTableAction::RenameColumn(old_name, new_name) => {
+            let renamed = position(&definition, &old_name.0)?;
+            ensure_unique(&definition, &new_name.0)?;
+            definition.columns[renamed].0 = new_name.0;
+        }
+        AlterTableAction::RenameTable(name) => definition.name = name,
+    }
+
+    Ok(definition)
+}
+
+fn push_current_layout(definition: &mut TableDefinition) -> BEResult<()> {
+    if definition.version() >= MAX_SCHEMA_VERSION {
+        return Err(BEErrors::InvalidSchemaChange(format!(
+            "table {} has reached the maximum number of schema versions",
+            definition.name.0
+        )));
+    }
+    let layout = definition.current_layout();
+    definition.schema_history.push(layout);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        database::Database,
+        fixtures::{run, select, temp_db_path, TempDbPath},
+    };
+
+    #[rstest]
+    fn test_alter_table(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table student(name text, age int);")?;
+        run(
+            &database,
+            "insert into student values (john, 21), (alice, 25);",
+        )?;
+        database.flush()?;
+
+        assert!(matches!(
+            run(&database, "alter table student add grade int;"),
+            Err(BEErrors::InvalidSchemaChange(_))
+        ));
+        assert!(matches!(
+            run(&database, "alter table student add grade int default high;"),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        run(&database, "alter table student add grade int default 1;")?;
+        run(&database, "insert into student values (bob, 22, 3);")?;
+        run(&database, "alter table student drop column age;")?;
+        run(
+            &database,
+            "alter table student rename column name to fullname;",
+        )?;
+        run(&database, "insert into student values (carol, 2);")?;
+        run(&database, "alter table student rename to pupil;")?;
+        assert!(matches!(
+            run(
+                &database,
+                "alter table pupil rename column grade to fullname;"
+            ),
+            Err(BEErrors::InvalidSchemaChange(_))
+        ));
+        database.flush()?;
+
+        assert!(!temp_db_path.join("meta").join("student").exists());
+        assert!(!temp_db_path.join("data").join("student").exists());
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let rows = select::<(String, i64)>(&reopened, "select fullname, grade from pupil;")?;
+        assert_eq!(
+            vec![
+                (String::from("john"), 1),
+                (String::from("alice"), 1),
+                (String::from("bob"), 3),
+                (String::from("carol"), 2),
+            ],
+            rows
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_rename_keeps_flushed_rows(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table student(name text, age int);")?;
+        run(&database, "insert into student values (john, 21);")?;
+        database.flush()?;
+        run(&database, "alter table student rename to pupil;")?;
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        assert_eq!(
+            vec![(String::from("john"), 21)],
+            select::<(String, i64)>(&reopened, "select name, age from pupil;")?
+        );
+        Ok(())
+    }
+}
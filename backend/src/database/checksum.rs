@@ -0,0 +1,59 @@
+//! CRC-32 as used by zlib and PNG, the reflected IEEE polynomial `0xedb88320`.
+
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// Running checksum over bytes given in one or more parts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Crc32(u32);
+
+impl Crc32 {
+    pub(crate) fn new() -> Crc32 {
+        Crc32(u32::MAX)
+    }
+
+    pub(crate) fn update(mut self, bytes: &[u8]) -> Crc32 {
+        for byte in bytes {
+            self.0 = TABLE[((self.0 ^ *byte as u32) & 0xff) as usize] ^ (self.0 >> 8);
+        }
+        self
+    }
+
+    pub(crate) fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(0, Crc32::new().finish());
+        assert_eq!(0xcbf4_3926, Crc32::new().update(b"123456789").finish());
+        assert_eq!(
+            Crc32::new().update(b"123456789").finish(),
+            Crc32::new().update(b"1234").update(b"56789").finish()
+        );
+    }
+}
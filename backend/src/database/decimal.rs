@@ -0,0 +1,235 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use frontend::ColumnType;
+
+/// Exact number stored as an integer count of `10^-scale` units, e.g. `12.30` is
+/// `1230` with a scale of 2.
+///
+/// Addition and subtraction keep the larger scale of their operands and are exact,
+/// multiplication adds the scales, division keeps the larger scale. Whenever digits
+/// have to be dropped the value is rounded half away from zero.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Decimal {
+    pub value: i128,
+    pub scale: u8,
+}
+
+const MAX_SCALE: u8 = ColumnType::MAX_DECIMAL_PRECISION;
+
+fn power_of_ten(exponent: u8) -> Option<i128> {
+    10i128.checked_pow(exponent as u32)
+}
+
+/// Divides rounding half away from zero, `None` when dividing by zero or overflowing.
+fn divide_rounded(numerator: i128, denominator: i128) -> Option<i128> {
+    let quotient = numerator.checked_div(denominator)?;
+    let remainder = (numerator % denominator).unsigned_abs();
+    if remainder >= denominator.unsigned_abs() - remainder {
+        let away_from_zero = if (numerator < 0) == (denominator < 0) {
+            1
+        } else {
+            -1
+        };
+        quotient.checked_add(away_from_zero)
+    } else {
+        Some(quotient)
+    }
+}
+
+impl Decimal {
+    /// Parses numbers like `12`, `-0.5` or `.25`, keeping every fractional digit given.
+    pub(crate) fn parse(value: &str) -> Option<Decimal> {
+        let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|it| it.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let scale = u8::try_from(fraction.len())
+            .ok()
+            .filter(|it| *it <= MAX_SCALE)?;
+        let mut unscaled = 0i128;
+        for digit in integer.bytes().chain(fraction.bytes()) {
+            unscaled = unscaled
+                .checked_mul(10)?
+                .checked_add((digit - b'0') as i128)?;
+        }
+        let value = if value.starts_with('-') {
+            -unscaled
+        } else {
+            unscaled
+        };
+        Some(Decimal { value, scale })
+    }
+
+    pub(crate) fn from_int(value: i64) -> Decimal {
+        Decimal {
+            value: value as i128,
+            scale: 0,
+        }
+    }
+
+    /// Brings the value to `scale`, rounding when digits are dropped.
+    pub(crate) fn rescale(self, scale: u8) -> Option<Decimal> {
+        let value = match scale.cmp(&self.scale) {
+            Ordering::Equal => self.value,
+            Ordering::Greater => self.value.checked_mul(power_of_ten(scale - self.scale)?)?,
+            Ordering::Less => divide_rounded(self.value, power_of_ten(self.scale - scale)?)?,
+        };
+        Some(Decimal { value, scale })
+    }
+
+    /// Whether the value has no more than `precision` digits in total.
+    pub(crate) fn fits(&self, precision: u8) -> bool {
+        power_of_ten(precision).is_some_and(|limit| self.value.unsigned_abs() < limit as u128)
+    }
+
+    pub(crate) fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(other.scale);
+        let value = self
+            .rescale(scale)?
+            .value
+            .checked_add(other.rescale(scale)?.value)?;
+        Some(Decimal { value, scale })
+    }
+
+    pub(crate) fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        self.checked_add(Decimal {
+            value: other.value.checked_neg()?,
+            scale: other.scale,
+        })
+    }
+
+    pub(crate) fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let product = Decimal {
+            value: self.value.checked_mul(other.value)?,
+            scale: self.scale + other.scale,
+        };
+        product.rescale(product.scale.min(MAX_SCALE))
+    }
+
+    /// `None` when `other` is zero or the quotient overflows.
+    pub(crate) fn checked_div(self, other: Decimal) -> Option<Decimal> {
+        let scale = self.scale.max(other.scale);
+        // self.value * 10^(scale + other.scale - self.scale) / other.value has `scale` digits
+        let numerator = self
+            .value
+            .checked_mul(power_of_ten(scale + other.scale - self.scale)?)?;
+        let value = divide_rounded(numerator, other.value)?;
+        Some(Decimal { value, scale })
+    }
+
+    pub(crate) fn to_f64(self) -> f64 {
+        self.value as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    // integer part and the fraction spread over MAX_SCALE digits, which order like the
+    // value whatever the scale, without rescaling either side into overflow
+    fn ordering_key(&self) -> (i128, i128) {
+        let unit = 10i128.pow(self.scale as u32);
+        let fraction = self.value % unit * 10i128.pow((MAX_SCALE - self.scale) as u32);
+        (self.value / unit, fraction)
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ordering_key().cmp(&other.ordering_key())
+    }
+}
+
+// equal values are equal whatever their scale, like 2.5 and 2.50
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.value < 0 { "-" } else { "" };
+        let digits = self.value.unsigned_abs();
+        if self.scale == 0 {
+            return write!(f, "{sign}{digits}");
+        }
+        let unit = 10u128.pow(self.scale as u32);
+        write!(
+            f,
+            "{sign}{}.{:0width$}",
+            digits / unit,
+            digits % unit,
+            width = self.scale as usize
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::parse(value).unwrap()
+    }
+
+    #[rstest]
+    #[case("12.30", 1230, 2, "12.30")]
+    #[case("-0.5", -5, 1, "-0.5")]
+    #[case(".25", 25, 2, "0.25")]
+    #[case("7.", 7, 0, "7")]
+    #[case("+42", 42, 0, "42")]
+    fn test_parse(
+        #[case] input: &str,
+        #[case] value: i128,
+        #[case] scale: u8,
+        #[case] display: &str,
+    ) {
+        let parsed = decimal(input);
+        assert_eq!((value, scale), (parsed.value, parsed.scale));
+        assert_eq!(display, parsed.to_string());
+    }
+
+    #[test]
+    fn test_arithmetic_and_rounding() {
+        assert_eq!(None, Decimal::parse("1.2.3"));
+        assert_eq!(None, Decimal::parse("-"));
+        assert_eq!(None, Decimal::parse("1e5"));
+
+        assert_eq!("1.01", decimal("1.005").rescale(2).unwrap().to_string());
+        assert_eq!("-1.01", decimal("-1.005").rescale(2).unwrap().to_string());
+        assert_eq!("1.50", decimal("1.5").rescale(2).unwrap().to_string());
+        assert!(decimal("999.99").fits(5));
+        assert!(!decimal("1000.00").fits(5));
+
+        let add = decimal("0.1").checked_add(decimal("0.20")).unwrap();
+        assert_eq!("0.30", add.to_string());
+        let sub = decimal("1").checked_sub(decimal("1.25")).unwrap();
+        assert_eq!("-0.25", sub.to_string());
+        let mul = decimal("1.10").checked_mul(decimal("3.3")).unwrap();
+        assert_eq!("3.630", mul.to_string());
+        let div = decimal("10.00").checked_div(decimal("3")).unwrap();
+        assert_eq!("3.33", div.to_string());
+        let div = decimal("2.00").checked_div(decimal("-3")).unwrap();
+        assert_eq!("-0.67", div.to_string());
+        assert_eq!(None, decimal("1").checked_div(decimal("0.00")));
+
+        assert!(decimal("1.5") > decimal("1.49"));
+        assert!(decimal("-1.5") < decimal("-1.2"));
+        assert!(decimal("-0.5") < decimal("0.2"));
+        assert_eq!(Ordering::Equal, decimal("2.50").cmp(&decimal("2.5")));
+    }
+}
@@ -0,0 +1,674 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use frontend::{Column, ColumnType};
+
+use crate::errors::{BEErrors, BEResult};
+
+use super::Decimal;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M:%S%.f";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// Column value parsed to the representation it is stored with in a page.
+/// Intervals only come up while evaluating expressions and are never stored.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Field {
+    Int(i64),
+    Text(String),
+    Real(f64),
+    Bool(bool),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Timestamp(NaiveDateTime),
+    Decimal(Decimal),
+    Blob(Vec<u8>),
+    Interval(Interval),
+}
+
+/// Span of time as calendar months plus an exact number of microseconds,
+/// as a month has no fixed length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Interval {
+    pub months: i32,
+    pub micros: i64,
+}
+
+impl Field {
+    /// Parses the textual `value` given for `column` according to the column type.
+    pub(crate) fn parse(column: &Column, value: String) -> BEResult<Field> {
+        let mismatched = |type_name, value| {
+            Err(BEErrors::MismatchedDataType(
+                column.0.clone(),
+                type_name,
+                value,
+            ))
+        };
+        match column.1 {
+            ColumnType::Int => match value.parse::<i64>() {
+                Ok(value) => Ok(Field::Int(value)),
+                Err(_) => mismatched("Int", value),
+            },
+            ColumnType::Text => Ok(Field::Text(value)),
+            ColumnType::Varchar(length) | ColumnType::Char(length) => {
+                let chars = value.chars().count();
+                if chars > length as usize {
+                    return Err(BEErrors::ValueTooLong(
+                        column.0.clone(),
+                        column.1.clone(),
+                        value,
+                    ));
+                }
+                match column.1 {
+                    ColumnType::Char(_) => Ok(Field::Text(pad_char(value, length))),
+                    _ => Ok(Field::Text(value)),
+                }
+            }
+            // nan and infinities can not be compared or ordered, so they are not stored
+            ColumnType::Real => match value.parse::<f64>() {
+                Ok(parsed) if parsed.is_finite() => Ok(Field::Real(parsed)),
+                _ => mismatched("Real", value),
+            },
+            ColumnType::Bool => match Field::parse_bool(&value) {
+                Some(value) => Ok(Field::Bool(value)),
+                None => mismatched("Bool", value),
+            },
+            ColumnType::Date => match Field::parse_date(&value) {
+                Some(date) => Ok(Field::Date(date)),
+                None => mismatched("Date", value),
+            },
+            ColumnType::Time => match Field::parse_time(&value) {
+                Some(time) => Ok(Field::Time(time)),
+                None => mismatched("Time", value),
+            },
+            ColumnType::Timestamp => match Field::parse_timestamp(&value) {
+                Some(timestamp) => Ok(Field::Timestamp(timestamp)),
+                None => mismatched("Timestamp", value),
+            },
+            // blobs are given as hex literals, see `Field::from_bytes`, never as text
+            ColumnType::Blob => mismatched("Blob", value),
+            ColumnType::Decimal(precision, scale) => {
+                let Some(decimal) = Decimal::parse(&value) else {
+                    return mismatched("Decimal", value);
+                };
+                match decimal.rescale(scale) {
+                    Some(decimal) if decimal.fits(precision) => Ok(Field::Decimal(decimal)),
+                    _ => Err(BEErrors::PrecisionExceeded(
+                        column.0.clone(),
+                        column.1.clone(),
+                        value,
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Parses the `true` and `false` literals, ignoring case.
+    pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+        if value.eq_ignore_ascii_case("true") {
+            Some(true)
+        } else if value.eq_ignore_ascii_case("false") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Parses an ISO-8601 date like `2024-02-29`.
+    pub(crate) fn parse_date(value: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(value, DATE_FORMAT).ok()
+    }
+
+    /// Parses an ISO-8601 time like `13:45`, `13:45:10` or `13:45:10.5`.
+    pub(crate) fn parse_time(value: &str) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(value, TIME_FORMAT)
+            .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+            .ok()
+            .map(truncate_to_micros)
+    }
+
+    /// Parses an ISO-8601 timestamp like `2024-02-29T13:45:10`, also accepting a space
+    /// instead of the `T`, a trailing `Z` and a plain date meaning midnight.
+    pub(crate) fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+        let value = value.strip_suffix('Z').unwrap_or(value);
+        let (date, time) = match value.split_once(['T', ' ']) {
+            Some((date, time)) => (Field::parse_date(date)?, Field::parse_time(time)?),
+            None => (Field::parse_date(value)?, NaiveTime::MIN),
+        };
+        Some(date.and_time(time))
+    }
+
+    /// Takes the bytes of a hex literal like `x'cafe'` given for `column`, which only
+    /// blob columns accept.
+    pub(crate) fn from_bytes(column: &Column, bytes: Vec<u8>) -> BEResult<Field> {
+        match column.1 {
+            ColumnType::Blob => Ok(Field::Blob(bytes)),
+            _ => Err(BEErrors::MismatchedDataType(
+                column.0.clone(),
+                type_name(&column.1),
+                BlobHex(&bytes).to_string(),
+            )),
+        }
+    }
+
+    /// Day number counting 0001-01-01 as day 1, which dates are stored as.
+    pub(crate) fn date_to_days(date: NaiveDate) -> i32 {
+        date.num_days_from_ce()
+    }
+
+    pub(crate) fn date_from_days(days: i32) -> BEResult<NaiveDate> {
+        NaiveDate::from_num_days_from_ce_opt(days)
+            .ok_or_else(|| BEErrors::InternalError(format!("date of day {days} is out of range")))
+    }
+
+    /// Microseconds since midnight, which times are stored as.
+    pub(crate) fn time_to_micros(time: NaiveTime) -> i64 {
+        time.num_seconds_from_midnight() as i64 * 1_000_000 + time.nanosecond() as i64 / 1_000
+    }
+
+    pub(crate) fn time_from_micros(micros: i64) -> BEResult<NaiveTime> {
+        u32::try_from(micros / 1_000_000)
+            .ok()
+            .zip(u32::try_from(micros % 1_000_000 * 1_000).ok())
+            .and_then(|(seconds, nanos)| {
+                NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanos)
+            })
+            .ok_or_else(|| {
+                BEErrors::InternalError(format!("time of {micros} microseconds is out of range"))
+            })
+    }
+
+    /// Microseconds since the unix epoch, which timestamps are stored as.
+    pub(crate) fn timestamp_to_micros(timestamp: NaiveDateTime) -> i64 {
+        timestamp.and_utc().timestamp_micros()
+    }
+
+    pub(crate) fn timestamp_from_micros(micros: i64) -> BEResult<NaiveDateTime> {
+        DateTime::from_timestamp_micros(micros)
+            .map(|it| it.naive_utc())
+            .ok_or_else(|| {
+                BEErrors::InternalError(format!(
+                    "timestamp of {micros} microseconds is out of range"
+                ))
+            })
+    }
+}
+
+/// Pads `value` with spaces to the `length` of a `char(length)` column.
+pub(crate) fn pad_char(mut value: String, length: u32) -> String {
+    let chars = value.chars().count();
+    value.extend(std::iter::repeat_n(
+        ' ',
+        (length as usize).saturating_sub(chars),
+    ));
+    value
+}
+
+/// Name of the type values of `column_type` are reported as.
+pub(crate) fn type_name(column_type: &ColumnType) -> &'static str {
+    match column_type {
+        ColumnType::Int => "Int",
+        ColumnType::Text | ColumnType::Varchar(_) | ColumnType::Char(_) => "Text",
+        ColumnType::Real => "Real",
+        ColumnType::Bool => "Bool",
+        ColumnType::Date => "Date",
+        ColumnType::Time => "Time",
+        ColumnType::Timestamp => "Timestamp",
+        ColumnType::Decimal(..) => "Decimal",
+        ColumnType::Blob => "Blob",
+    }
+}
+
+/// Renders bytes in the `\xcafe` form blobs are shown in.
+pub(crate) struct BlobHex<'a>(pub &'a [u8]);
+
+impl Display for BlobHex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\\x")?;
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// Drops the nanoseconds which do not survive being stored as microseconds.
+pub(crate) fn truncate_to_micros<T: Timelike>(value: T) -> T {
+    let nanosecond = value.nanosecond() / 1_000 * 1_000;
+    value.with_nanosecond(nanosecond).unwrap_or(value)
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Field::Int(value) => write!(f, "{value}"),
+            Field::Text(value) => write!(f, "{value}"),
+            // debug formatting keeps the fraction of whole numbers, e.g. 3.0 instead of 3
+            Field::Real(value) => write!(f, "{value:?}"),
+            Field::Bool(value) => write!(f, "{value}"),
+            Field::Date(value) => write!(f, "{}", value.format(DATE_FORMAT)),
+            Field::Time(value) => write!(f, "{}", value.format(TIME_FORMAT)),
+            Field::Timestamp(value) => write!(f, "{}", value.format(TIMESTAMP_FORMAT)),
+            Field::Decimal(value) => write!(f, "{value}"),
+            Field::Blob(value) => write!(f, "{}", BlobHex(value)),
+            Field::Interval(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl Interval {
+    const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+    /// Parses intervals like `1 day`, `2 hours 30 minutes` or `-1 year 2 months`.
+    pub(crate) fn parse(value: &str) -> BEResult<Interval> {
+        let invalid = || BEErrors::InvalidExpression(format!("{value} is not an interval"));
+        let parts = value.split_whitespace().collect::<Vec<_>>();
+        if parts.is_empty() || parts.len() % 2 != 0 {
+            return Err(invalid());
+        }
+
+        let mut interval = Interval::default();
+        for part in parts.chunks(2) {
+            let amount = part[0].parse::<i64>().map_err(|_| invalid())?;
+            let unit = part[1].to_lowercase();
+            let unit = unit.strip_suffix('s').unwrap_or(&unit);
+            let (months, micros) = match unit {
+                "year" => (12, 0),
+                "month" | "mon" => (1, 0),
+                "week" => (0, 7 * Interval::MICROS_PER_DAY),
+                "day" => (0, Interval::MICROS_PER_DAY),
+                "hour" => (0, 3_600_000_000),
+                "minute" | "min" => (0, 60_000_000),
+                "second" | "sec" => (0, 1_000_000),
+                "millisecond" => (0, 1_000),
+                "microsecond" => (0, 1),
+                _ => return Err(invalid()),
+            };
+            let months = amount
+                .checked_mul(months)
+                .and_then(|it| i32::try_from(it).ok())
+                .ok_or_else(invalid)?;
+            interval.months = interval.months.checked_add(months).ok_or_else(invalid)?;
+            interval.micros = amount
+                .checked_mul(micros)
+                .and_then(|it| interval.micros.checked_add(it))
+                .ok_or_else(invalid)?;
+        }
+        Ok(interval)
+    }
+
+    /// Approximate length in microseconds, counting months as 30 days, used for ordering.
+    pub(crate) fn approximate_micros(&self) -> i128 {
+        self.months as i128 * 30 * Interval::MICROS_PER_DAY as i128 + self.micros as i128
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = self.micros / Interval::MICROS_PER_DAY;
+        // less than a day, which is always a time
+        let time = Field::time_from_micros((self.micros % Interval::MICROS_PER_DAY).abs())
+            .map_err(|_| std::fmt::Error)?;
+        let sign = if self.micros < 0 && days == 0 {
+            "-"
+        } else {
+            ""
+        };
+        write!(
+            f,
+            "{} months {days} days {sign}{}",
+            self.months,
+            time.format(TIME_FORMAT)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use frontend::definitions::table_definition::TableName;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        database::Database,
+        fixtures::{query, run, temp_db_path, TempDbPath},
+    };
+
+    #[rstest]
+    fn test_real_columns(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(
+            &database,
+            "create table product(name text, price real, qty int);",
+        )?;
+        run(
+            &database,
+            "insert into product values (pen, 1.5, 10), (book, 12, 2), (bag, 2.5e1, 1);",
+        )?;
+        assert!(matches!(
+            run(&database, "insert into product values (cup, cheap, 1);"),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        for price in ["nan", "inf", "infinity"] {
+            assert!(matches!(
+                run(
+                    &database,
+                    &format!("insert into product values (cup, {price}, 1);")
+                ),
+                Err(BEErrors::MismatchedDataType(..))
+            ));
+        }
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let select =
+            |sql: &str| -> BEResult<Vec<(String, f64)>> { crate::fixtures::select(&reopened, sql) };
+        assert_eq!(
+            vec![(String::from("book"), 12.0), (String::from("bag"), 25.0)],
+            select("select name, price from product where price * qty > 20;")?
+        );
+        assert_eq!(
+            vec![(String::from("pen"), 1.5), (String::from("book"), 12.0)],
+            select("select name, price from product where qty > 1.5;")?
+        );
+        assert_eq!(
+            vec![(String::from("pen"), 1.5)],
+            select("select name, price from product where price < qty / 4;")?
+        );
+        assert!(matches!(
+            select("select name, price from product where qty / 0 > 1;"),
+            Err(BEErrors::InvalidExpression(_))
+        ));
+        assert!(matches!(
+            select("select name, price from product where price / 0 > 1;"),
+            Err(BEErrors::InvalidExpression(_))
+        ));
+        assert!(matches!(
+            select("select name, price from product where price < 'nan';"),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_bool_columns(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(
+            &database,
+            "create table users(name text, active boolean, age int);",
+        )?;
+        run(
+            &database,
+            "insert into users values (john, true, 21), (alice, FALSE, 25), (bob, true, 30);",
+        )?;
+        assert!(matches!(
+            run(&database, "insert into users values (carol, 1, 22);"),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let select = |sql: &str| -> BEResult<Vec<(String, bool)>> {
+            crate::fixtures::select(&reopened, sql)
+        };
+        assert_eq!(
+            vec![(String::from("john"), true), (String::from("bob"), true)],
+            select("select name, active from users where active;")?
+        );
+        assert_eq!(
+            vec![(String::from("alice"), false)],
+            select("select name, active from users where not active;")?
+        );
+        assert_eq!(
+            vec![(String::from("bob"), true)],
+            select("select name, active from users where (age > 21) = active;")?
+        );
+        assert!(matches!(
+            select("select name, active from users where age;"),
+            Err(BEErrors::InvalidExpression(_))
+        ));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_temporal_columns(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(
+            &database,
+            "create table event(name text, day date, starts time, created timestamp);",
+        )?;
+        run(
+            &database,
+            "insert into event values (launch, '2024-02-28', '09:30', '2024-02-28T09:30:00.250'), (review, '2024-03-01', '14:00:05', '2024-03-01 14:00:05Z');",
+        )?;
+        assert!(matches!(
+            run(
+                &database,
+                "insert into event values (party, '2024-02-30', '20:00', '2024-02-30');"
+            ),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let select = |sql: &str| -> BEResult<Vec<(String, String)>> {
+            crate::fixtures::select(&reopened, sql)
+        };
+        assert_eq!(
+            vec![
+                (
+                    String::from("launch"),
+                    String::from("2024-02-28T09:30:00.250")
+                ),
+                (String::from("review"), String::from("2024-03-01T14:00:05"))
+            ],
+            select("select name, created from event where created < now();")?
+        );
+        assert_eq!(
+            vec![(String::from("review"), String::from("2024-03-01"))],
+            select("select name, day from event where day + 1 > '2024-03-01';")?
+        );
+        assert_eq!(
+            vec![(String::from("launch"), String::from("09:30:00"))],
+            select(
+                "select name, starts from event where created + interval '1 day 12 hours' < '2024-03-01' and starts < '12:00';"
+            )?
+        );
+        assert_eq!(
+            vec![(String::from("review"), String::from("2024-03-01"))],
+            select(
+                "select name, day from event where date_part('month', day) = 3 and date_part('dow', created) = 5;"
+            )?
+        );
+        assert_eq!(
+            vec![(String::from("review"), String::from("2024-03-01"))],
+            select("select name, day from event where created - day > interval '14 hours';")?
+        );
+        assert_eq!(
+            vec![(String::from("review"), String::from("2024-03-01"))],
+            select(
+                "select name, day from event where created >= '2024-01-31' + interval '1 month';"
+            )?
+        );
+        assert!(matches!(
+            select("select name, day from event where day + starts > now();"),
+            Err(BEErrors::InvalidExpression(_))
+        ));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_decimal_columns(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(
+            &database,
+            "create table account(name text, balance decimal(8, 2));",
+        )?;
+        run(
+            &database,
+            "insert into account values (alice, 10.005), (bob, 123456.7), (carol, -0.5);",
+        )?;
+        assert!(matches!(
+            run(&database, "insert into account values (dave, 1234567);"),
+            Err(BEErrors::PrecisionExceeded(..))
+        ));
+        assert!(matches!(
+            run(&database, "insert into account values (dave, rich);"),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let select = |sql: &str| -> BEResult<Vec<(String, String)>> {
+            crate::fixtures::select(&reopened, sql)
+        };
+        assert_eq!(
+            vec![
+                (String::from("alice"), String::from("10.01")),
+                (String::from("bob"), String::from("123456.70")),
+                (String::from("carol"), String::from("-0.50"))
+            ],
+            select("select name, balance from account;")?
+        );
+        assert_eq!(
+            vec![(String::from("alice"), String::from("10.01"))],
+            select("select name, balance from account where balance * 3 = 30.03 and balance + 0.1 = 10.11;")?
+        );
+        assert_eq!(
+            vec![(String::from("bob"), String::from("123456.70"))],
+            select("select name, balance from account where balance / 3 = 41152.23;")?
+        );
+        assert_eq!(
+            vec![
+                (String::from("alice"), String::from("10.01")),
+                (String::from("bob"), String::from("123456.70"))
+            ],
+            select("select name, balance from account where balance > 10.005;")?
+        );
+        assert!(matches!(
+            select("select name, balance from account where balance / 0 > 1;"),
+            Err(BEErrors::InvalidExpression(_))
+        ));
+
+        let rows = query(
+            &reopened,
+            "select name, balance from account where balance < 0;",
+        )?;
+        assert_eq!(
+            vec![(String::from("carol"), -0.5)],
+            rows.deserialize::<(String, f64)>()
+                .collect::<BEResult<Vec<_>>>()?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_blob_columns(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table file(name text, data bytea);")?;
+        run(
+            &database,
+            "insert into file values (logo, x'CAFE'), (empty, x''), (header, X'00ff10');",
+        )?;
+        database
+            .prepare("insert into file values (?, ?);")?
+            .execute(&["raw".into(), vec![0xff, 0xfe, 0x00].into()])?;
+        for statement in [
+            "insert into file values (text, 'cafe');",
+            r"insert into file values (text, '\xcafe');",
+            "insert into file values (x'cafe', x'cafe');",
+        ] {
+            assert!(matches!(
+                run(&database, statement),
+                Err(BEErrors::MismatchedDataType(..))
+            ));
+        }
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let select = |sql: &str| -> BEResult<Vec<(String, String)>> {
+            crate::fixtures::select(&reopened, sql)
+        };
+        assert_eq!(
+            vec![
+                (String::from("logo"), String::from("\\xcafe")),
+                (String::from("empty"), String::from("\\x")),
+                (String::from("header"), String::from("\\x00ff10")),
+                (String::from("raw"), String::from("\\xfffe00"))
+            ],
+            select("select name, data from file;")?
+        );
+        assert_eq!(
+            vec![(String::from("logo"), String::from("\\xcafe"))],
+            select("select name, data from file where data = x'cafe';")?
+        );
+
+        let rows = query(
+            &reopened,
+            "select name, data from file where data > x'00' and data < x'ff';",
+        )?;
+        assert_eq!(
+            vec![
+                (String::from("logo"), vec![0xca, 0xfe]),
+                (String::from("header"), vec![0x00, 0xff, 0x10])
+            ],
+            rows.deserialize::<(String, Vec<u8>)>()
+                .collect::<BEResult<Vec<_>>>()?
+        );
+
+        run(&reopened, "create table copy(name text, data blob);")?;
+        run(
+            &reopened,
+            "insert into copy select name, data from file where name = 'logo';",
+        )?;
+        assert_eq!(
+            vec![(String::from("logo"), String::from("\\xcafe"))],
+            select("select name, data from copy where data = x'cafe';")?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_character_columns(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(
+            &database,
+            "create table person(code char(3), name varchar(5));",
+        )?;
+        run(
+            &database,
+            "insert into person values (ab, alice), ('é', 'zoë'), (xyz, bob);",
+        )?;
+        assert!(matches!(
+            run(&database, "insert into person values (abcd, carl);"),
+            Err(BEErrors::ValueTooLong(..))
+        ));
+        assert!(matches!(
+            run(&database, "insert into person values (ab, alexander);"),
+            Err(BEErrors::ValueTooLong(..))
+        ));
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let definition = reopened.get_table_definition(&TableName(String::from("person")))?;
+        assert_eq!(ColumnType::Char(3), definition.columns[0].1);
+        assert_eq!(ColumnType::Varchar(5), definition.columns[1].1);
+
+        let select = |sql: &str| -> BEResult<Vec<(String, String)>> {
+            crate::fixtures::select(&reopened, sql)
+        };
+        assert_eq!(
+            vec![
+                (String::from("ab "), String::from("alice")),
+                (String::from("é  "), String::from("zoë"))
+            ],
+            select("select code, name from person where code = 'ab' or code = 'é';")?
+        );
+        assert!(select("select code, name from person where name = 'alexander';")?.is_empty());
+        Ok(())
+    }
+}
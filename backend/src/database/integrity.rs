@@ -0,0 +1,348 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+};
+
+use frontend::{definitions::table_definition::TableName, TableDefinition};
+
+use crate::{
+    disk::DiskAccessor,
+    errors::{BEErrors, BEResult},
+};
+
+use super::{
+    legacy,
+    page::{Page, StoredRecord},
+    table::Table,
+    Database, DEFAULT_PAGE_SIZE, RECORD_FORMAT_VERSION,
+};
+
+/// Inconsistency found by [`super::Database::check_integrity`], located as closely as
+/// the check allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityProblem {
+    pub table: String,
+    pub page: Option<usize>,
+    pub slot: Option<usize>,
+    pub message: String,
+}
+
+impl Display for IntegrityProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "table {}", self.table)?;
+        if let Some(page) = self.page {
+            write!(f, ", page {page}")?;
+        }
+        if let Some(slot) = self.slot {
+            write!(f, ", slot {slot}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Reads the files of the database through `disk_accessor` and checks them, reporting
+/// definitions which can not be read and pages which are cut short or do not match their
+/// checksum along with everything [`check`] finds in the rest.
+pub(super) fn check_files(disk_accessor: &DiskAccessor) -> BEResult<Vec<IntegrityProblem>> {
+    if disk_accessor.has_interrupted_flush() {
+        return Err(BEErrors::ReadOnlyDatabase(String::from(
+            "finish an interrupted flush",
+        )));
+    }
+    let (format_version, page_size) = Database::stored_format(disk_accessor)?;
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    let mut problems = Vec::new();
+    let mut definitions = HashMap::new();
+    let mut unreadable = HashSet::new();
+    for name in disk_accessor.table_files("meta")? {
+        let name = TableName(name);
+        let definition = disk_accessor.read_table_file("meta", &name.0)?;
+        match serde_json::from_slice::<TableDefinition>(&definition) {
+            Ok(definition) => {
+                definitions.insert(name, Arc::new(definition));
+            }
+            Err(err) => {
+                problems.push(problem(
+                    &name,
+                    None,
+                    None,
+                    format!("definition can not be read, {err}"),
+                ));
+                unreadable.insert(name);
+            }
+        }
+    }
+
+    let mut tables = HashMap::new();
+    for name in disk_accessor.table_files("data")? {
+        let name = TableName(name);
+        let data = disk_accessor.read_table_file("data", &name.0)?;
+        let table = if format_version == RECORD_FORMAT_VERSION {
+            load_pages(&name, &data, page_size, &mut problems)
+        } else {
+            // pages of older formats are checked as converted, which fails on the first
+            // one which can not be read
+            let converted = definitions
+                .get(&name)
+                .map(|definition| legacy::convert_table(&data, definition, page_size));
+            match converted {
+                Some(Ok(table)) => table,
+                Some(Err(err)) => {
+                    problems.push(problem(&name, None, None, err.to_string()));
+                    continue;
+                }
+                None => Table::new(page_size),
+            }
+        };
+        tables.insert(name, table);
+    }
+
+    problems.extend(check(&definitions, &unreadable, &tables));
+    Ok(problems)
+}
+
+// splits a data file into pages like `Table::load`, but reports the pages which are not
+// intact instead of failing and puts empty pages in their place
+fn load_pages(
+    name: &TableName,
+    data: &[u8],
+    page_size: usize,
+    problems: &mut Vec<IntegrityProblem>,
+) -> Table {
+    let mut table = Table::new(page_size);
+    for (index, page) in data.chunks(page_size).enumerate() {
+        if Page::is_intact(page, page_size) {
+            table.pages.push(Page::new(page.to_vec(), page_size));
+            continue;
+        }
+        let message = if page.len() < page_size {
+            "page is cut short"
+        } else {
+            "page does not match its checksum"
+        };
+        problems.push(problem(name, Some(index), None, message));
+        table.pages.push(Page::slotted(page_size));
+    }
+    table
+}
+
+/// Checks every table against its definition. There are no indexes yet, so there is
+/// nothing to check the tables against beyond their definitions. Tables in `unreadable`
+/// have a definition which was already reported as unreadable.
+fn check(
+    definitions: &HashMap<TableName, Arc<TableDefinition>>,
+    unreadable: &HashSet<TableName>,
+    tables: &HashMap<TableName, Table>,
+) -> Vec<IntegrityProblem> {
+    let mut problems = Vec::new();
+    for name in definitions.keys() {
+        if !tables.contains_key(name) {
+            problems.push(problem(name, None, None, "table has no data file"));
+        }
+    }
+
+    let mut names = tables.keys().collect::<Vec<_>>();
+    names.sort_by(|first, second| first.0.cmp(&second.0));
+    for name in names {
+        let definition = definitions.get(name);
+        if definition.is_none() && !unreadable.contains(name) {
+            problems.push(problem(name, None, None, "data without a table definition"));
+        }
+        check_table(name, &tables[name], definition, &mut problems);
+    }
+    problems
+}
+
+fn check_table(
+    name: &TableName,
+    table: &Table,
+    definition: Option<&Arc<TableDefinition>>,
+    problems: &mut Vec<IntegrityProblem>,
+) {
+    // the record each overflow page is reached from
+    let mut owners: Vec<Option<(usize, usize)>> = vec![None; table.pages.len()];
+    for (index, page) in table.pages.iter().enumerate() {
+        let layout = page.check_layout();
+        let broken = !layout.is_empty();
+        for (slot, message) in layout {
+            problems.push(problem(name, Some(index), slot, message));
+        }
+        if broken {
+            continue;
+        }
+
+        for slot in 0..page.row_count() {
+            if let Ok((StoredRecord::Overflow { first_page, .. }, _)) = page.stored_record(slot) {
+                let mut next = Some(first_page);
+                while let Some(overflow) =
+                    next.filter(|it| table.pages.get(*it).is_some_and(|page| page.is_overflow()))
+                {
+                    if let Some((page, slot)) = owners[overflow] {
+                        problems.push(problem(
+                            name,
+                            Some(overflow),
+                            None,
+                            format!("overflow page is shared with page {page}, slot {slot}"),
+                        ));
+                        break;
+                    }
+                    owners[overflow] = Some((index, slot));
+                    next = table.pages[overflow].overflow_next();
+                }
+            }
+            if let Some(definition) = definition {
+                if let Err(err) = table.read_record(index, slot, definition) {
+                    problems.push(problem(name, Some(index), Some(slot), err.to_string()));
+                }
+            }
+        }
+    }
+
+    for (index, page) in table.pages.iter().enumerate() {
+        if page.is_overflow() && owners[index].is_none() {
+            problems.push(problem(
+                name,
+                Some(index),
+                None,
+                "overflow page is not part of any record",
+            ));
+        }
+    }
+}
+
+fn problem(
+    name: &TableName,
+    page: Option<usize>,
+    slot: Option<usize>,
+    message: impl Into<String>,
+) -> IntegrityProblem {
+    IntegrityProblem {
+        table: name.0.clone(),
+        page,
+        slot,
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, str::FromStr};
+
+    use frontend::command::statement::StatementCommand;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        database::{page::stamp_checksum, record, Field},
+        fixtures::{temp_db_path, TempDbPath},
+    };
+
+    #[rstest]
+    fn test_check_integrity(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        for statement in [
+            "create table student(id int, name text);",
+            "insert into student values (1, john), (2, alice);",
+        ] {
+            database.execute(StatementCommand::from_str(statement)?)?;
+        }
+        database.insert("student", &(3, "x".repeat(10_000)))?;
+        database.flush()?;
+        assert_eq!(Vec::<IntegrityProblem>::new(), database.check_integrity()?);
+        drop(database);
+
+        let size = DEFAULT_PAGE_SIZE;
+        let path = temp_db_path.join("data/student");
+        let mut data = fs::read(&path)?;
+        // point the second slot past the end of the page, with the checksum to match
+        let mut first = Page::new(data[..size].to_vec(), size);
+        first.set_slot_offset(1, size);
+        data[..size].copy_from_slice(first.get_data());
+        stamp_checksum(&mut data[..size]);
+        // a row the definition can not decode, text that is not utf-8
+        let mut page = Page::slotted(size);
+        page.write_record(
+            &record::encode(&[Field::Int(4), Field::Blob(vec![0xff])])?,
+            0,
+        )?;
+        let mut undecodable = page.get_data().to_vec();
+        stamp_checksum(&mut undecodable);
+        data.extend(undecodable);
+        let mut orphan = Page::overflow(&[0; 10], None, size)?.get_data().to_vec();
+        stamp_checksum(&mut orphan);
+        data.extend(orphan);
+        // two pages whose bytes changed after they were written
+        for id in [5, 6] {
+            let mut page = Page::slotted(size);
+            page.write_record(&record::encode(&[Field::Int(id)])?, 0)?;
+            let mut page = page.get_data().to_vec();
+            stamp_checksum(&mut page);
+            page[size - 1] ^= 0xff;
+            data.extend(page);
+        }
+        // and a last one cut short
+        data.extend([0; 100]);
+        fs::write(&path, data)?;
+
+        assert!(matches!(
+            Database::open(&temp_db_path),
+            Err(BEErrors::CorruptedPage(_, 6))
+        ));
+        let problems = Database::check_integrity_at(&temp_db_path)?;
+        let located = problems
+            .iter()
+            .map(|it| (it.page, it.slot))
+            .collect::<Vec<_>>();
+        // the large row's overflow pages are only reachable from the broken page
+        assert_eq!(
+            vec![
+                (Some(6), None),
+                (Some(7), None),
+                (Some(8), None),
+                (Some(0), Some(1)),
+                (Some(4), Some(0)),
+                (Some(1), None),
+                (Some(2), None),
+                (Some(3), None),
+                (Some(5), None),
+            ],
+            located
+        );
+        assert_eq!(
+            "table student, page 7: page does not match its checksum",
+            problems[1].to_string()
+        );
+        assert_eq!(
+            "table student, page 8: page is cut short",
+            problems[2].to_string()
+        );
+        assert_eq!(
+            "table student, page 5: overflow page is not part of any record",
+            problems[8].to_string()
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_unreadable_definition_is_reported_once(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        for statement in [
+            "create table student(id int, name text);",
+            "insert into student values (1, john);",
+        ] {
+            database.execute(StatementCommand::from_str(statement)?)?;
+        }
+        database.flush()?;
+        drop(database);
+        fs::write(temp_db_path.join("meta/student"), "{")?;
+
+        let problems = Database::check_integrity_at(&temp_db_path)?;
+        assert_eq!(1, problems.len());
+        assert!(problems[0]
+            .to_string()
+            .starts_with("table student: definition can not be read"));
+        Ok(())
+    }
+}
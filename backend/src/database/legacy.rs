@@ -0,0 +1,270 @@
+//! Converter for data files written before the record encoding was fixed.
+//!
+//! Their pages have [`LEGACY_PAGE_SIZE`] and hold the slot count, the offset the next
+//! record is written at and at most 21 slot entries as `u32`, followed by the records
+//! written front to back from byte 648 on. Only `int` and `text` columns existed, and a
+//! record holds the values in the order its insert statement listed the columns, without
+//! saying which column each value belongs to. Ints and text lengths are aligned to their
+//! own size within the page, all in the native byte order of the machine writing them,
+//! with text lengths stored as native `usize`. They can only be read back on a machine
+//! of the same byte order and pointer width, which is the one converting them.
+
+use std::collections::HashSet;
+
+use frontend::{ColumnType, TableDefinition};
+
+use crate::errors::{BEErrors, BEResult};
+
+use super::{record::Reader, table::Table, Field};
+
+/// Size of the pages written before the page size was configurable.
+pub(super) const LEGACY_PAGE_SIZE: usize = 4096;
+
+/// Kind of value a legacy record holds, one for each column type there was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Int,
+    Text,
+}
+
+/// Decodes every record of a legacy data file and writes it into a table in the current
+/// format with pages of `page_size`.
+pub(super) fn convert_table(
+    data: &[u8],
+    table_definition: &TableDefinition,
+    page_size: usize,
+) -> BEResult<Table> {
+    let kinds = table_definition
+        .columns
+        .iter()
+        .map(|column| match column.1 {
+            ColumnType::Int => Ok(Kind::Int),
+            ColumnType::Text => Ok(Kind::Text),
+            _ => Err(BEErrors::InternalError(format!(
+                "column {} of {} is of a type legacy data files do not hold",
+                column.0, table_definition.name.0
+            ))),
+        })
+        .collect::<BEResult<Vec<_>>>()?;
+
+    let mut table = Table::new(page_size);
+    for page in data.chunks(LEGACY_PAGE_SIZE) {
+        let mut header = Reader {
+            bytes: page,
+            offset: 0,
+        };
+        let slot_count = u32::from_ne_bytes(header.read_array()?) as usize;
+        let free_offset = u32::from_ne_bytes(header.read_array()?) as usize;
+        let offsets = (0..slot_count)
+            .map(|_| Ok(u32::from_ne_bytes(header.read_array()?) as usize))
+            .collect::<BEResult<Vec<_>>>()?;
+
+        // records follow each other, the last one ending where the next would be written
+        let ends = offsets.iter().skip(1).copied().chain([free_offset]);
+        for (start, end) in offsets.iter().copied().zip(ends) {
+            let Some(record) = page.get(..end).filter(|_| start <= end) else {
+                return Err(BEErrors::InternalError(format!(
+                    "record at {start} of {} overruns the page",
+                    table_definition.name.0
+                )));
+            };
+            let fields = read(record, start, &kinds).ok_or_else(|| {
+                BEErrors::InternalError(format!(
+                    "record at {start} of {} does not hold a value for every column",
+                    table_definition.name.0
+                ))
+            })?;
+            table.write_fields(fields, table_definition.version())?;
+        }
+    }
+    Ok(table)
+}
+
+/// Reads the record starting at `start`, with `record` being the page up to its end,
+/// with one value for each of the columns of `kinds`. The order the values were listed in is found by
+/// trying which order of ints and texts takes up the record exactly, the column order
+/// first. Values of the same kind can not be told apart and are taken to be listed in
+/// column order.
+fn read(record: &[u8], start: usize, kinds: &[Kind]) -> Option<Vec<Field>> {
+    let ints = kinds.iter().filter(|it| **it == Kind::Int).count();
+    let mut order = Order {
+        record,
+        kinds,
+        values: Vec::new(),
+        dead_ends: HashSet::new(),
+    };
+    if !order.search(start, ints, kinds.len() - ints) {
+        return None;
+    }
+
+    // the n-th value of a kind belongs to the n-th column of that kind
+    let mut fields = vec![None; kinds.len()];
+    for kind in [Kind::Int, Kind::Text] {
+        let columns = kinds.iter().enumerate().filter(|it| *it.1 == kind);
+        let values = order.values.iter().filter(|it| it.0 == kind);
+        for ((column, _), (_, field)) in columns.zip(values) {
+            fields[column] = Some(field.clone());
+        }
+    }
+    fields.into_iter().collect()
+}
+
+/// Search for the order of the values in a record, remembering the offsets and counts
+/// of values left which do not lead to its end.
+struct Order<'a> {
+    record: &'a [u8],
+    kinds: &'a [Kind],
+    values: Vec<(Kind, Field)>,
+    dead_ends: HashSet<(usize, usize, usize)>,
+}
+
+impl Order<'_> {
+    fn search(&mut self, offset: usize, ints: usize, texts: usize) -> bool {
+        if ints == 0 && texts == 0 {
+            return offset == self.record.len();
+        }
+        if self.dead_ends.contains(&(offset, ints, texts)) {
+            return false;
+        }
+        let preferred = self.kinds[self.values.len()];
+        let other = match preferred {
+            Kind::Int => Kind::Text,
+            Kind::Text => Kind::Int,
+        };
+        for kind in [preferred, other] {
+            let (left_ints, left_texts) = match kind {
+                Kind::Int if ints > 0 => (ints - 1, texts),
+                Kind::Text if texts > 0 => (ints, texts - 1),
+                _ => continue,
+            };
+            let mut reader = Reader {
+                bytes: self.record,
+                offset,
+            };
+            let Ok(field) = read_value(&mut reader, kind) else {
+                continue;
+            };
+            self.values.push((kind, field));
+            if self.search(reader.offset, left_ints, left_texts) {
+                return true;
+            }
+            self.values.pop();
+        }
+        self.dead_ends.insert((offset, ints, texts));
+        false
+    }
+}
+
+fn read_value(reader: &mut Reader, kind: Kind) -> BEResult<Field> {
+    match kind {
+        Kind::Int => Ok(Field::Int(i64::from_ne_bytes(read_aligned(reader)?))),
+        Kind::Text => {
+            let len = usize::from_ne_bytes(read_aligned(reader)?);
+            let text = String::from_utf8(reader.take(len)?.to_vec()).map_err(|err| {
+                BEErrors::InternalError(format!("text in page is not utf-8, {err}"))
+            })?;
+            Ok(Field::Text(text))
+        }
+    }
+}
+
+// values were aligned to their size counting from the start of the page
+fn read_aligned<const N: usize>(reader: &mut Reader) -> BEResult<[u8; N]> {
+    reader.offset = reader.offset.next_multiple_of(N);
+    reader.read_array()
+}
+
+// the fixture was written by the baseline on x86_64, which the pages are native to
+#[cfg(all(test, target_endian = "little", target_pointer_width = "64"))]
+mod test {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        database::{Database, DEFAULT_PAGE_SIZE},
+        fixtures::{select, temp_db_path, TempDbPath},
+    };
+
+    // a student table as written by the baseline, with rows 4 and 5 inserted listing the
+    // name before or after both ints and the unused bytes of the second page left as
+    // they were
+    const DEFINITION: &str = include_str!("../fixtures/legacy/meta/student");
+    const DATA: &[u8] = include_bytes!("../fixtures/legacy/data/student");
+
+    fn students() -> Vec<(i64, String, i64)> {
+        let mut students = vec![
+            (1, String::from("john"), 21),
+            (2, String::from("satish"), 22),
+            (3, String::from("alice"), 21),
+            (4, String::from("bob"), 30),
+            (5, String::from("zoe"), 25),
+        ];
+        students.extend((6..25).map(|id| (id, format!("student{id}"), id + 10)));
+        students
+    }
+
+    #[test]
+    fn test_convert_legacy_pages() -> BEResult<()> {
+        let definition = serde_json::from_str::<TableDefinition>(DEFINITION)?;
+        assert_eq!(2, DATA.len() / LEGACY_PAGE_SIZE);
+
+        let table = convert_table(DATA, &definition, DEFAULT_PAGE_SIZE)?;
+        let (table, definition) = (&table, &definition);
+        let records = table
+            .pages
+            .iter()
+            .enumerate()
+            .flat_map(|(index, page)| {
+                (0..page.row_count()).map(move |slot| table.read_record(index, slot, definition))
+            })
+            .collect::<BEResult<Vec<_>>>()?;
+        let expected = students()
+            .into_iter()
+            .map(|(id, name, age)| vec![Field::Int(id), Field::Text(name), Field::Int(age)])
+            .collect::<Vec<_>>();
+        assert_eq!(expected, records);
+
+        let definition =
+            TableDefinition::from_str("create table student (id int, name text, age real);")?;
+        assert!(matches!(
+            convert_table(DATA, &definition, DEFAULT_PAGE_SIZE),
+            Err(BEErrors::InternalError(_))
+        ));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_open_converts_legacy_database(temp_db_path: TempDbPath) -> BEResult<()> {
+        std::fs::create_dir_all(temp_db_path.join("meta"))?;
+        std::fs::create_dir_all(temp_db_path.join("data"))?;
+        std::fs::write(temp_db_path.join("meta/student"), DEFINITION)?;
+        std::fs::write(temp_db_path.join("data/student"), DATA)?;
+
+        let database = Database::open(&temp_db_path)?;
+        database.flush()?;
+        assert_eq!(
+            r#"{"format_version":1,"page_size":4096}"#,
+            std::fs::read_to_string(temp_db_path.join("database.json"))?
+        );
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        assert_eq!(
+            students(),
+            select::<(i64, String, i64)>(&reopened, "select id, name, age from student;")?
+        );
+        drop(reopened);
+
+        std::fs::write(
+            temp_db_path.join("database.json"),
+            r#"{"format_version":2}"#,
+        )?;
+        assert!(matches!(
+            Database::open(&temp_db_path),
+            Err(BEErrors::UnsupportedFormat(2))
+        ));
+        Ok(())
+    }
+}
@@ -1,84 +1,258 @@
+mod alter;
+mod checksum;
+mod decimal;
+mod field;
+mod integrity;
+mod legacy;
+mod options;
 mod page;
+mod prepared;
+mod record;
+mod rows;
 mod table;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::PathBuf,
+    str::FromStr,
     sync::{Arc, Mutex},
 };
 
 use frontend::{
-    command::statement::insert::Value, definitions::table_definition::TableName, InsertStatement,
-    SelectStatement, TableDefinition,
+    command::statement::{insert::Value, StatementCommand},
+    definitions::table_definition::TableName,
+    AlterTableAction, AlterTableStatement, Column, CreateTableAsStatement, CreateTableStatement,
+    DropTableStatement, InsertSelectStatement, InsertStatement, SelectStatement, TableDefinition,
+    TruncateTableStatement, VacuumStatement,
 };
+use serde::Serialize;
 
 use crate::{
     disk::{
-        data_iterator::DiskDataIterator, def_iterator::DiskTableDefinitionIterator, DiskAccessor,
+        data_iterator::DiskDataIterator, def_iterator::DiskTableDefinitionIterator,
+        DatabaseMetadata, DiskAccessor,
     },
     errors::{BEErrors, BEResult},
-    DATABASE,
+    mapping::to_values,
+    vm::{self, expression::evaluate_default, ExecutionResult},
 };
 
-use self::table::Table;
+pub use self::integrity::IntegrityProblem;
+pub use self::options::OpenOptions;
+pub use self::prepared::{Parameter, PreparedStatement};
+pub use self::rows::Rows;
+pub(crate) use self::{
+    decimal::Decimal,
+    field::{pad_char, truncate_to_micros, Field, Interval},
+    page::stamp_checksum,
+    record::RECORD_FORMAT_VERSION,
+};
+use self::{page::Page, table::Table};
 
 type Sharable<T> = Arc<Mutex<T>>;
-pub type Rows = Vec<Vec<Value>>;
-pub(super) const PAGE_SIZE: usize = 4096;
+pub type Row = Vec<Value>;
+pub(crate) const DEFAULT_PAGE_SIZE: usize = 4096;
+pub(super) const MIN_PAGE_SIZE: usize = 4096;
+// record offsets in slot entries have 16 bits
+pub(super) const MAX_PAGE_SIZE: usize = 64 * 1024;
 
-#[derive(Debug, Clone, Default)]
-pub(super) struct Database {
-    table_definitions: Sharable<HashMap<TableName, &'static TableDefinition>>,
+/// Handle to a database stored under a directory on disk.
+/// Cloning the handle is cheap and every clone shares the same in-memory state,
+/// while separately opened databases are fully independent of each other.
+#[derive(Debug, Clone)]
+pub struct Database {
+    table_definitions: Sharable<HashMap<TableName, Arc<TableDefinition>>>,
     tables: Sharable<HashMap<TableName, Table>>,
-    disk_accessor: Sharable<Option<DiskAccessor>>,
+    // Tables dropped since the last flush, whose files are removed on the next flush.
+    dropped_tables: Sharable<HashSet<TableName>>,
+    disk_accessor: Arc<DiskAccessor>,
+    page_size: usize,
+    read_only: bool,
 }
 
 impl Database {
-    pub(super) fn init_db_with_file(&self, base_path: PathBuf) -> BEResult<()> {
-        let mut disk_accessor = self.disk_accessor.lock().unwrap();
-        *disk_accessor = Some(DiskAccessor::new(base_path));
+    /// Opens the database at `base_path`, creating the directory layout if it does not exist
+    /// and loading all table definitions and pages already stored there.
+    /// Pages of databases written before the record encoding was fixed are converted while
+    /// loading and stored in the current encoding by the next flush.
+    /// The directory is locked until every clone of the handle is dropped, and opening it
+    /// again meanwhile, from this process or another, fails with [`BEErrors::DatabaseLocked`].
+    pub fn open(base_path: impl Into<PathBuf>) -> BEResult<Database> {
+        OpenOptions::new().open(base_path)
+    }
+
+    pub(super) fn open_with(base_path: PathBuf, options: &OpenOptions) -> BEResult<Database> {
+        let disk_accessor = DiskAccessor::new(base_path, options.read_only)?;
+        if !options.read_only {
+            disk_accessor.recover()?;
+        } else if disk_accessor.has_interrupted_flush() {
+            return Err(BEErrors::ReadOnlyDatabase(String::from(
+                "finish an interrupted flush",
+            )));
+        }
+        let (format_version, stored_page_size) = Database::stored_format(&disk_accessor)?;
+        let page_size = options::resolve_page_size(options, stored_page_size)?;
+
+        let database = Database {
+            table_definitions: Sharable::default(),
+            tables: Sharable::default(),
+            dropped_tables: Sharable::default(),
+            disk_accessor: Arc::new(disk_accessor),
+            page_size,
+            read_only: options.read_only,
+        };
 
-        let def_iterator = DiskTableDefinitionIterator::new(disk_accessor.as_ref().unwrap());
+        let def_iterator = DiskTableDefinitionIterator::new(&database.disk_accessor);
         for table_def in def_iterator.into_iter() {
-            DATABASE.add_table_definitions(table_def)?;
+            database.add_table_definitions(table_def)?;
         }
 
-        let data_iter = DiskDataIterator::new(disk_accessor.as_ref().unwrap());
+        let data_iter = DiskDataIterator::new(&database.disk_accessor);
         for (table_name, data) in data_iter.into_iter() {
-            let table = Table::new(data);
-            let mut tables = self.tables.lock().unwrap();
+            let table = if format_version == RECORD_FORMAT_VERSION {
+                Table::load(&table_name, data, page_size)?
+            } else {
+                let definition = database.get_table_definition(&table_name)?;
+                legacy::convert_table(&data, &definition, page_size)?
+            };
+            let mut tables = database.tables.lock().unwrap();
             tables.insert(table_name, table);
         }
 
-        Ok(())
+        Ok(database)
     }
 
+    /// Format version and page size of the files under `disk_accessor`, without a page
+    /// size for a new database which has neither metadata nor tables.
+    fn stored_format(disk_accessor: &DiskAccessor) -> BEResult<(u32, Option<usize>)> {
+        let metadata = disk_accessor.read_metadata()?;
+        let format_version = metadata.as_ref().map_or(0, |it| it.format_version);
+        if format_version > RECORD_FORMAT_VERSION {
+            return Err(BEErrors::UnsupportedFormat(format_version));
+        }
+        let has_tables = !disk_accessor.table_files("meta")?.is_empty();
+        // databases without metadata but with tables have pages of the legacy size
+        let page_size = match metadata {
+            Some(metadata) => Some(metadata.page_size),
+            None if has_tables => Some(legacy::LEGACY_PAGE_SIZE),
+            None => None,
+        };
+        Ok((format_version, page_size))
+    }
+
+    /// Executes a single parsed statement against this database.
+    pub fn execute(&self, command: StatementCommand) -> BEResult<ExecutionResult> {
+        vm::execute(self, command)
+    }
+
+    /// Parses `statement` once so it can be executed repeatedly with bound parameters.
+    pub fn prepare(&self, statement: &str) -> BEResult<PreparedStatement> {
+        PreparedStatement::new(self.clone(), StatementCommand::from_str(statement)?)
+    }
+
+    /// Inserts `record` as a row of `table_name`, with struct fields mapped to columns by name.
+    pub fn insert<T: Serialize + ?Sized>(
+        &self,
+        table_name: impl Into<String>,
+        record: &T,
+    ) -> BEResult<()> {
+        let statement = InsertStatement(TableName(table_name.into()), vec![to_values(record)?]);
+        self.execute(StatementCommand::Insert(statement))
+            .map(|_| ())
+    }
+
+    /// Checks the files of the database as of the last flush: that every page is whole
+    /// and matches its checksum, is laid out consistently, that every row decodes per its
+    /// table definition and that every overflow page belongs to exactly one row.
+    /// Returns all problems found, none for a consistent database.
+    pub fn check_integrity(&self) -> BEResult<Vec<IntegrityProblem>> {
+        integrity::check_files(&self.disk_accessor)
+    }
+
+    /// Checks the files of the database at `base_path` like [`Database::check_integrity`]
+    /// without opening it, so that databases which fail to open can be checked as well.
+    /// Fails with [`BEErrors::DatabaseLocked`] while the database is open for writing.
+    pub fn check_integrity_at(base_path: impl Into<PathBuf>) -> BEResult<Vec<IntegrityProblem>> {
+        let disk_accessor = DiskAccessor::new(base_path.into(), true)?;
+        integrity::check_files(&disk_accessor)
+    }
+
+    /// Whether the database was opened with [`OpenOptions::read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Writes all table definitions and pages held in memory to disk.
+    pub fn flush(&self) -> BEResult<()> {
+        self.flush_db()
+    }
+
+    /// Collects every change into a journal and commits it, so that after a crash the
+    /// database on disk is either as it was before the flush or as it is after it.
     pub(super) fn flush_db(&self) -> BEResult<()> {
+        self.flush_tables(None)
+    }
+
+    /// Flushes the changes to `only` the given tables, or to every table and the tables
+    /// dropped since the last flush. Other tables keep their changes for the next flush.
+    /// Only pages changed since they were last read or written are written again.
+    fn flush_tables(&self, only: Option<&[TableName]>) -> BEResult<()> {
+        if self.read_only {
+            return Err(BEErrors::ReadOnlyDatabase(String::from("flush")));
+        }
+        let included = |name: &TableName| only.is_none_or(|it| it.contains(name));
+        let mut journal = self.disk_accessor.begin_flush()?;
+
+        // taken without holding the lock, which `alter_table` takes after the others
+        let dropped_tables: Vec<_> = match only {
+            Some(_) => Vec::new(),
+            None => self
+                .dropped_tables
+                .lock()
+                .unwrap()
+                .iter()
+                .cloned()
+                .collect(),
+        };
+        for name in &dropped_tables {
+            journal.delete_table(name)?;
+        }
+
         {
             let tables = self.table_definitions.lock().unwrap();
-            let disk_accessor = self.disk_accessor.lock().unwrap();
-            for (name, def) in tables.iter() {
-                disk_accessor
-                    .as_ref()
-                    .unwrap()
-                    .write_table_definition(name, def)?;
+            for (name, def) in tables.iter().filter(|(name, _)| included(name)) {
+                journal.write_table_definition(name, def)?;
             }
         }
 
-        {
-            let tables = self.tables.lock().unwrap();
-            let disk_accessor = self.disk_accessor.lock().unwrap();
-
-            for (name, data) in tables.iter() {
-                for (index, page) in data.pages.iter().enumerate() {
-                    disk_accessor.as_ref().unwrap().write_data_page(
-                        name,
-                        index,
-                        page.get_data(),
-                    )?;
+        // held until the journal is committed, so no page changes between being written
+        // to the journal and being marked as flushed
+        let mut tables = self.tables.lock().unwrap();
+        for (name, data) in tables.iter().filter(|(name, _)| included(name)) {
+            for (index, page) in data.pages.iter().enumerate() {
+                if page.is_dirty() {
+                    journal.write_data_page(name, index, page.get_data())?;
                 }
             }
+            journal.truncate_data_file(name, data.pages.len() * self.page_size)?;
+        }
+
+        // written last, so that pages of an older format are only marked as converted
+        // once all of them are, which is why those are never flushed table by table
+        journal.write_metadata(&DatabaseMetadata {
+            format_version: RECORD_FORMAT_VERSION,
+            page_size: self.page_size,
+        })?;
+
+        self.disk_accessor.commit_flush(journal)?;
+        for (_, data) in tables.iter_mut().filter(|(name, _)| included(name)) {
+            data.pages.iter_mut().for_each(Page::mark_flushed);
         }
+        drop(tables);
 
+        let mut dropped = self.dropped_tables.lock().unwrap();
+        for name in &dropped_tables {
+            dropped.remove(name);
+        }
         Ok(())
     }
 
@@ -89,37 +263,576 @@ impl Database {
         }
 
         let table_name = definition.name.clone();
-        let definition = Box::new(definition);
-        let definition: &'static TableDefinition = Box::leak(definition);
-        definition_holder.insert(table_name.clone(), definition);
+        definition_holder.insert(table_name.clone(), Arc::new(definition));
 
         let mut tables = self.tables.lock().unwrap();
-        tables.insert(table_name, Table::default());
+        tables.insert(table_name, Table::new(self.page_size));
         Ok(())
     }
 
+    /// Adds the table, an existing table is kept as is when `if not exists` is given.
+    pub(super) fn create_table(&self, create_statement: CreateTableStatement) -> BEResult<()> {
+        let CreateTableStatement(definition, if_not_exists) = create_statement;
+        for column in &definition.columns {
+            evaluate_default(column)?;
+        }
+        match self.add_table_definitions(definition) {
+            Err(BEErrors::DuplicateDefinition(_)) if if_not_exists => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Creates a table with the selected columns and inserts the selected rows into it.
+    /// The table is removed again if the rows can not be inserted.
+    pub(super) fn create_table_as(&self, create_statement: CreateTableAsStatement) -> BEResult<()> {
+        let CreateTableAsStatement(table_name, if_not_exists, select_statement) = create_statement;
+        let source_definition = self.get_table_definition(&select_statement.0)?;
+        let columns = select_statement
+            .1
+            .iter()
+            .map(|name| {
+                source_definition
+                    .columns
+                    .iter()
+                    .find(|it| it.0 == name.0)
+                    .map(|it| Column(it.0.clone(), it.1.clone(), None))
+                    .ok_or_else(|| BEErrors::MissingColumn(format!("Column {} not found", name.0)))
+            })
+            .collect::<BEResult<Vec<_>>>()?;
+
+        let definition = TableDefinition {
+            name: table_name.clone(),
+            columns,
+            schema_history: Vec::new(),
+        };
+        match self.add_table_definitions(definition) {
+            Err(BEErrors::DuplicateDefinition(_)) if if_not_exists => return Ok(()),
+            result => result?,
+        }
+
+        let inserted = self.insert_selected_records(InsertSelectStatement(
+            table_name.clone(),
+            Vec::new(),
+            select_statement,
+        ));
+        if inserted.is_err() {
+            self.table_definitions.lock().unwrap().remove(&table_name);
+            self.tables.lock().unwrap().remove(&table_name);
+        }
+        inserted
+    }
+
     pub(super) fn insert_record(&self, insert_statement: InsertStatement) -> BEResult<()> {
         let table_definition = self.get_table_definition(&insert_statement.0)?;
 
         let mut tables = self.tables.lock().unwrap();
         let table = tables.get_mut(&insert_statement.0).unwrap();
-        table.write_row(insert_statement.1, table_definition)
+        table.write_rows(insert_statement.1, &table_definition)
+    }
+
+    /// Removes the table from memory, its files are deleted on the next flush.
+    pub(super) fn drop_table(&self, drop_statement: DropTableStatement) -> BEResult<()> {
+        let DropTableStatement(table_name, if_exists) = drop_statement;
+        let mut definition_holder = self.table_definitions.lock().unwrap();
+        if definition_holder.remove(&table_name).is_none() {
+            if if_exists {
+                return Ok(());
+            }
+            return Err(BEErrors::MissingTable(table_name.0));
+        }
+
+        self.tables.lock().unwrap().remove(&table_name);
+        self.dropped_tables.lock().unwrap().insert(table_name);
+        Ok(())
+    }
+
+    /// Applies a schema change, rows already stored keep their layout and are mapped
+    /// to the new columns when read.
+    pub(super) fn alter_table(&self, alter_statement: AlterTableStatement) -> BEResult<()> {
+        let AlterTableStatement(table_name, action) = alter_statement;
+        let mut definition_holder = self.table_definitions.lock().unwrap();
+        let Some(definition) = definition_holder.get(&table_name) else {
+            return Err(BEErrors::MissingTable(table_name.0));
+        };
+        let mut tables = self.tables.lock().unwrap();
+        let has_rows = tables
+            .get(&table_name)
+            .is_some_and(|table| table.pages.iter().any(|page| page.row_count() > 0));
+
+        if let AlterTableAction::RenameTable(new_name) = &action {
+            if definition_holder.contains_key(new_name) {
+                return Err(BEErrors::DuplicateDefinition(new_name.clone()));
+            }
+        }
+        let altered = alter::alter_columns(definition, action, has_rows)?;
+        if let Some(table) = tables.get_mut(&table_name) {
+            table.generation += 1;
+        }
+
+        if altered.name != table_name {
+            definition_holder.remove(&table_name);
+            let mut table = tables
+                .remove(&table_name)
+                .unwrap_or_else(|| Table::new(self.page_size));
+            // the files of the old name are deleted, so every page is written anew
+            table.pages.iter_mut().for_each(Page::mark_dirty);
+            tables.insert(altered.name.clone(), table);
+            let mut dropped_tables = self.dropped_tables.lock().unwrap();
+            dropped_tables.remove(&altered.name);
+            dropped_tables.insert(table_name);
+        }
+        definition_holder.insert(altered.name.clone(), Arc::new(altered));
+        Ok(())
+    }
+
+    /// Removes every row of the table while keeping its definition.
+    pub(super) fn truncate_table(
+        &self,
+        truncate_statement: TruncateTableStatement,
+    ) -> BEResult<()> {
+        let table_name = truncate_statement.0;
+        self.get_table_definition(&table_name)?;
+
+        let mut tables = self.tables.lock().unwrap();
+        let table = tables
+            .entry(table_name)
+            .or_insert_with(|| Table::new(self.page_size));
+        table.pages.clear();
+        table.generation += 1;
+        Ok(())
+    }
+
+    /// Rewrites the rows of the table, or of every table, into densely packed pages and
+    /// writes them to disk, which shrinks their data files to the pages still in use.
+    /// Vacuuming a single table leaves the changes to other tables to the next flush,
+    /// unless the database still has pages of an older format, which are all converted
+    /// by the same flush. There are no indexes yet, so there is nothing else to rebuild.
+    pub(super) fn vacuum(&self, vacuum_statement: VacuumStatement) -> BEResult<()> {
+        let table_names = match &vacuum_statement.0 {
+            Some(table_name) => vec![table_name.clone()],
+            None => self
+                .table_definitions
+                .lock()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect(),
+        };
+
+        for table_name in &table_names {
+            let definition = self.get_table_definition(table_name)?;
+            let mut tables = self.tables.lock().unwrap();
+            let compacted = match tables.get(table_name) {
+                Some(table) => table.compacted(&definition)?,
+                None => Table::new(self.page_size),
+            };
+            tables.insert(table_name.clone(), compacted);
+        }
+
+        let (format_version, _) = Database::stored_format(&self.disk_accessor)?;
+        let has_older_pages = format_version != RECORD_FORMAT_VERSION
+            && !self.disk_accessor.table_files("meta")?.is_empty();
+        match vacuum_statement.0 {
+            Some(_) if !has_older_pages => self.flush_tables(Some(&table_names)),
+            _ => self.flush_db(),
+        }
+    }
+
+    /// Inserts every row produced by the select, validating all of them before writing any.
+    pub(super) fn insert_selected_records(
+        &self,
+        insert_select_statement: InsertSelectStatement,
+    ) -> BEResult<()> {
+        let InsertSelectStatement(table_name, columns, select_statement) = insert_select_statement;
+        let selected_columns = select_statement.1.len();
+        if !columns.is_empty() && columns.len() != selected_columns {
+            return Err(BEErrors::ColumnCountMismatch(
+                columns.len(),
+                selected_columns,
+            ));
+        }
+
+        let rows = self
+            .select_records(select_statement)?
+            .map(|row| {
+                row.map(|values| {
+                    values
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, value)| match (columns.get(index), value) {
+                            (Some(column), Value::NamedBlob(_, bytes)) => {
+                                Value::NamedBlob(column.0.clone(), bytes)
+                            }
+                            (None, Value::NamedBlob(_, bytes)) => Value::UnnamedBlob(bytes),
+                            (Some(column), value) => {
+                                Value::NamedValue(column.0.clone(), value.value())
+                            }
+                            (None, value) => Value::UnnamedValue(value.value()),
+                        })
+                        .collect()
+                })
+            })
+            .collect::<BEResult<Vec<_>>>()?;
+
+        self.insert_record(InsertStatement(table_name, rows))
     }
 
     pub(super) fn select_records(&self, select_statement: SelectStatement) -> BEResult<Rows> {
         let table_definition = self.get_table_definition(&select_statement.0)?;
-        let tables = self.tables.lock().unwrap();
-        let table = tables.get(&select_statement.0).unwrap();
-
-        table.read_pages(select_statement.1, table_definition)
+        Rows::new(
+            self.table_definitions.clone(),
+            self.tables.clone(),
+            select_statement.0,
+            select_statement.1,
+            select_statement.2,
+            table_definition,
+        )
     }
 
-    fn get_table_definition(&self, table_name: &TableName) -> BEResult<&'static TableDefinition> {
+    fn get_table_definition(&self, table_name: &TableName) -> BEResult<Arc<TableDefinition>> {
         let definition_holder = self.table_definitions.lock().unwrap();
-        let Some(&table_definition) = definition_holder.get(&table_name) else {
+        let Some(table_definition) = definition_holder.get(table_name) else {
             return Err(BEErrors::MissingTable(table_name.0.clone()));
         };
 
-        Ok(table_definition)
+        Ok(Arc::clone(table_definition))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use rstest::rstest;
+
+    use super::*;
+    use crate::fixtures::{
+        query, run, select, temp_database, temp_db_path, TempDatabase, TempDbPath,
+    };
+
+    #[rstest]
+    fn test_independent_databases(
+        #[from(temp_db_path)] first_path: TempDbPath,
+        #[from(temp_db_path)] second_path: TempDbPath,
+    ) -> BEResult<()> {
+        let first = Database::open(&first_path)?;
+        let second = Database::open(&second_path)?;
+
+        run(&first, "create table student(id int, name text);")?;
+        run(&first, "insert into student (id, name) values (1, john);")?;
+        assert!(matches!(
+            run(&second, "select id, name from student;"),
+            Err(BEErrors::MissingTable(_))
+        ));
+
+        run(&second, "create table student(id int, name text);")?;
+        let rows = query(&second, "select id, name from student;")?;
+        assert_eq!(0, rows.count());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_database_is_locked_while_open(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        let clone = database.clone();
+        assert!(matches!(
+            Database::open(&temp_db_path),
+            Err(BEErrors::DatabaseLocked(_))
+        ));
+
+        // the lock is released once every handle sharing it is dropped
+        drop(database);
+        assert!(matches!(
+            Database::open(&temp_db_path),
+            Err(BEErrors::DatabaseLocked(_))
+        ));
+        drop(clone);
+        Database::open(&temp_db_path)?;
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_reopen_after_flush(temp_db_path: TempDbPath) -> BEResult<()> {
+        {
+            let database = Database::open(&temp_db_path)?;
+            run(&database, "create table student(id int, name text);")?;
+            run(
+                &database,
+                "insert into student (id, name) values (1, john);",
+            )?;
+            database.flush()?;
+        }
+
+        let database = Database::open(&temp_db_path)?;
+        let rows = query(&database, "select id, name from student;")?;
+        assert_eq!(
+            vec![vec![
+                Value::NamedValue(String::from("id"), String::from("1")),
+                Value::NamedValue(String::from("name"), String::from("john")),
+            ]],
+            rows.collect::<BEResult<Vec<_>>>()?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_insert_select_with_filter(
+        #[from(temp_database)] database: TempDatabase,
+    ) -> BEResult<()> {
+        run(
+            &database,
+            "create table student(id int, name text, age int);",
+        )?;
+        run(&database, "create table alumni(name text, id int);")?;
+        run(
+            &database,
+            "insert into student values (1, john, 21), (2, 'satish k', 22), (3, alice, 25);",
+        )?;
+
+        run(
+            &database,
+            "insert into alumni (id, name) select id, name from student where age > 21;",
+        )?;
+        assert!(matches!(
+            run(
+                &database,
+                "insert into alumni select name, name from student;"
+            ),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        let ExecutionResult::SelectResult(mut rows) =
+            run(&database, "select name from student where age > 'old';")?
+        else {
+            panic!("select should return rows");
+        };
+        assert!(matches!(
+            rows.next(),
+            Some(Err(BEErrors::MismatchedDataType(..)))
+        ));
+
+        let select = database.prepare("select name from alumni where id = ? or name = $2;")?;
+        let ExecutionResult::SelectResult(rows) = select.execute(&[3.into(), "satish k".into()])?
+        else {
+            panic!("select should return rows");
+        };
+        let names = rows
+            .deserialize::<(String,)>()
+            .collect::<BEResult<Vec<_>>>()?;
+        assert_eq!(
+            vec![(String::from("satish k"),), (String::from("alice"),)],
+            names
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_drop_and_truncate(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table student(name text, age int);")?;
+        run(&database, "create table alumni(name text, age int);")?;
+        run(
+            &database,
+            "insert into student values (john, 21), (alice, 25);",
+        )?;
+        run(&database, "insert into alumni values (harry, 30);")?;
+        database.flush()?;
+
+        run(&database, "drop table alumni;")?;
+        run(&database, "truncate table student;")?;
+        assert!(matches!(
+            run(&database, "drop table alumni;"),
+            Err(BEErrors::MissingTable(_))
+        ));
+        run(&database, "drop table if exists alumni;")?;
+        assert!(matches!(
+            run(&database, "truncate table alumni;"),
+            Err(BEErrors::MissingTable(_))
+        ));
+        database.flush()?;
+
+        assert!(!temp_db_path.join("meta").join("alumni").exists());
+        assert!(!temp_db_path.join("data").join("alumni").exists());
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        assert!(matches!(
+            run(&reopened, "select name from alumni;"),
+            Err(BEErrors::MissingTable(_))
+        ));
+        let rows = query(&reopened, "select name from student;")?;
+        assert_eq!(0, rows.count());
+
+        run(&reopened, "insert into student values (ron, 22);")?;
+        let rows = query(&reopened, "select name from student;")?;
+        assert_eq!(1, rows.count());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_default_expressions(#[from(temp_database)] database: TempDatabase) -> BEResult<()> {
+        run(
+            &database,
+            "create table item(name text, stock int default 1 + 1, change int default -(5), due timestamp default '2024-01-30' + interval '2 days');",
+        )?;
+        run(&database, "insert into item (name) values (pen);")?;
+        assert!(matches!(
+            run(&database, "create table broken(count int default 'many');"),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        assert!(run(&database, "alter table item add price real default 1 / 0;").is_err());
+        // old rows would read a different value each time
+        assert!(run(
+            &database,
+            "alter table item add seen timestamp default now();"
+        )
+        .is_err());
+
+        let rows = select::<(String, i64, i64, String)>(
+            &database,
+            "select name, stock, change, due from item;",
+        )?;
+        assert_eq!(
+            vec![(
+                String::from("pen"),
+                2,
+                -5,
+                String::from("2024-02-01T00:00:00")
+            )],
+            rows
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_vacuum(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table note(id int, body text);")?;
+        run(&database, "create table empty(id int);")?;
+        let body = "a".repeat(100);
+        for id in 0..300 {
+            run(
+                &database,
+                &format!("insert into note values ({id}, '{body}');"),
+            )?;
+        }
+        // rows written before dropping the column keep its bytes until rewritten
+        run(&database, "alter table note drop column body;")?;
+        database.flush()?;
+        let data_path = temp_db_path.join("data/note");
+        let size = std::fs::metadata(&data_path)?.len();
+
+        assert!(matches!(
+            run(&database, "vacuum missing;"),
+            Err(BEErrors::MissingTable(_))
+        ));
+        run(&database, "vacuum note;")?;
+        assert!(std::fs::metadata(&data_path)?.len() < size);
+        assert_eq!(
+            vec![String::from("note"), String::from("empty")]
+                .into_iter()
+                .collect::<HashSet<_>>(),
+            std::fs::read_dir(temp_db_path.join("data"))?
+                .map(|it| Ok(it?.file_name().to_string_lossy().to_string()))
+                .collect::<BEResult<HashSet<_>>>()?
+        );
+        run(&database, "vacuum;")?;
+        assert!(database.check_integrity()?.is_empty());
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let rows = query(&reopened, "select id from note where id >= 298;")?;
+        assert_eq!(
+            vec![(298,), (299,)],
+            rows.deserialize::<(i64,)>().collect::<BEResult<Vec<_>>>()?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_vacuum_table_keeps_other_changes(temp_db_path: TempDbPath) -> BEResult<()> {
+        let ids = |database: &Database, table: &str| -> BEResult<Vec<(i64,)>> {
+            let rows = query(database, &format!("select id from {table};"))?;
+            rows.deserialize::<(i64,)>().collect()
+        };
+
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table note(id int);")?;
+        run(&database, "create table draft(id int);")?;
+        run(&database, "insert into note values (1);")?;
+        run(&database, "insert into draft values (1);")?;
+        // neither table was ever flushed
+        run(&database, "vacuum note;")?;
+        drop(database);
+
+        let database = Database::open(&temp_db_path)?;
+        assert_eq!(vec![(1,)], ids(&database, "note")?);
+        assert!(matches!(
+            ids(&database, "draft"),
+            Err(BEErrors::MissingTable(_))
+        ));
+        run(&database, "create table draft(id int);")?;
+        run(&database, "insert into draft values (1);")?;
+        database.flush()?;
+        run(&database, "insert into note values (2);")?;
+        run(&database, "insert into draft values (2);")?;
+        run(&database, "drop table draft;")?;
+        run(&database, "vacuum note;")?;
+        drop(database);
+
+        let database = Database::open(&temp_db_path)?;
+        assert_eq!(vec![(1,), (2,)], ids(&database, "note")?);
+        assert_eq!(vec![(1,)], ids(&database, "draft")?);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_flush_writes_changed_pages(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table student(id int, name text);")?;
+        for id in 0..400 {
+            run(
+                &database,
+                &format!("insert into student (id, name) values ({id}, student{id});"),
+            )?;
+        }
+        let name = TableName(String::from("student"));
+        let dirty_pages = |database: &Database| {
+            database.tables.lock().unwrap()[&name]
+                .pages
+                .iter()
+                .filter(|it| it.is_dirty())
+                .count()
+        };
+        database.flush()?;
+        assert_eq!(0, dirty_pages(&database));
+
+        // a first page rewritten by the next flush would no longer be zeroed
+        let data_path = temp_db_path.join("data/student");
+        let mut data = std::fs::read(&data_path)?;
+        let page_count = data.len() / DEFAULT_PAGE_SIZE;
+        assert!(page_count > 1);
+        data[..DEFAULT_PAGE_SIZE].fill(0);
+        std::fs::write(&data_path, &data)?;
+
+        run(
+            &database,
+            "insert into student (id, name) values (400, last);",
+        )?;
+        assert_eq!(1, dirty_pages(&database));
+        database.flush()?;
+        assert_eq!(0, dirty_pages(&database));
+
+        let data = std::fs::read(&data_path)?;
+        assert_eq!(page_count * DEFAULT_PAGE_SIZE, data.len());
+        assert!(data[..DEFAULT_PAGE_SIZE].iter().all(|it| *it == 0));
+        let last_page = &data[(page_count - 1) * DEFAULT_PAGE_SIZE..];
+        assert!(Page::is_intact(last_page, DEFAULT_PAGE_SIZE));
+        // compared past the header, as the checksum is only filled in on disk
+        assert_eq!(
+            database.tables.lock().unwrap()[&name].pages[page_count - 1].get_data()[16..],
+            last_page[16..]
+        );
+
+        drop(database);
+        Ok(())
     }
 }
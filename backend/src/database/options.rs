@@ -0,0 +1,243 @@
+use std::path::PathBuf;
+
+use crate::errors::{BEErrors, BEResult};
+
+use super::{Database, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, MIN_PAGE_SIZE};
+
+/// Options for opening a database, with [`Database::open`] taking the defaults.
+///
+/// ```no_run
+/// use backend::OpenOptions;
+///
+/// let database = OpenOptions::new().page_size(16 * 1024).open("analytics")?;
+/// # Ok::<(), backend::BEErrors>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    pub(super) page_size: Option<usize>,
+    pub(super) read_only: bool,
+}
+
+impl OpenOptions {
+    pub fn new() -> OpenOptions {
+        OpenOptions::default()
+    }
+
+    /// Size of the pages a new database is created with, a power of two between 4096
+    /// and 65536. Existing databases keep the page size they were created with and fail
+    /// to open with a different one.
+    pub fn page_size(mut self, page_size: usize) -> OpenOptions {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Opens an existing database without ever writing to it, so statements other than
+    /// selects and flushes fail with [`BEErrors::ReadOnlyDatabase`]. Any number of
+    /// read-only handles can be open at once, but none while the database is open for
+    /// writing.
+    pub fn read_only(mut self, read_only: bool) -> OpenOptions {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn open(&self, base_path: impl Into<PathBuf>) -> BEResult<Database> {
+        Database::open_with(base_path.into(), self)
+    }
+}
+
+/// Rejects page sizes a database can not be created with.
+pub(super) fn check_page_size(page_size: usize) -> BEResult<()> {
+    if !page_size.is_power_of_two() || !(MIN_PAGE_SIZE..=MAX_PAGE_SIZE).contains(&page_size) {
+        return Err(BEErrors::InvalidPageSize(page_size));
+    }
+    Ok(())
+}
+
+/// Page size of a database whose pages are of `stored` bytes, `None` for new databases.
+pub(super) fn resolve_page_size(options: &OpenOptions, stored: Option<usize>) -> BEResult<usize> {
+    if let Some(page_size) = options.page_size {
+        check_page_size(page_size)?;
+    }
+    match (options.page_size, stored) {
+        (Some(requested), Some(stored)) if requested != stored => {
+            Err(BEErrors::PageSizeMismatch(requested, stored))
+        }
+        (_, Some(stored)) => {
+            check_page_size(stored)?;
+            Ok(stored)
+        }
+        (requested, None) => Ok(requested.unwrap_or(DEFAULT_PAGE_SIZE)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::fixtures::{query, run, select, temp_db_path, TempDbPath};
+
+    #[rstest]
+    fn test_read_only(temp_db_path: TempDbPath) -> BEResult<()> {
+        assert!(matches!(
+            OpenOptions::new().read_only(true).open(&temp_db_path),
+            Err(BEErrors::IoError(_))
+        ));
+        assert!(!temp_db_path.exists());
+
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table student(id int, name text);")?;
+        run(&database, "insert into student values (1, john);")?;
+        database.flush()?;
+        assert!(matches!(
+            OpenOptions::new().read_only(true).open(&temp_db_path),
+            Err(BEErrors::DatabaseLocked(_))
+        ));
+        drop(database);
+
+        let read_only = OpenOptions::new().read_only(true);
+        let first = read_only.open(&temp_db_path)?;
+        let second = read_only.open(&temp_db_path)?;
+        assert!(first.is_read_only());
+        assert!(matches!(
+            Database::open(&temp_db_path),
+            Err(BEErrors::DatabaseLocked(_))
+        ));
+        let rows = query(&second, "select id from student;")?;
+        assert_eq!(1, rows.count());
+        for statement in [
+            "insert into student values (2, jane);",
+            "create table teacher(id int);",
+            "drop table student;",
+            "truncate table student;",
+            "vacuum;",
+        ] {
+            assert!(matches!(
+                run(&first, statement),
+                Err(BEErrors::ReadOnlyDatabase(_))
+            ));
+        }
+        assert!(matches!(first.flush(), Err(BEErrors::ReadOnlyDatabase(_))));
+        drop((first, second));
+
+        // a flush left to finish would write to the database
+        std::fs::write(temp_db_path.join("flush.journal"), [])?;
+        assert!(matches!(
+            read_only.open(&temp_db_path),
+            Err(BEErrors::ReadOnlyDatabase(_))
+        ));
+        drop(Database::open(&temp_db_path)?);
+        read_only.open(&temp_db_path)?;
+
+        // databases copied without their lock file are opened without writing one
+        std::fs::remove_file(temp_db_path.join("database.lock"))?;
+        let unlocked = read_only.open(&temp_db_path)?;
+        assert!(!temp_db_path.join("database.lock").exists());
+        drop(unlocked);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_create_options(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        let create = "create table if not exists student(name text, age int default 18, city text default 'new york');";
+        run(&database, create)?;
+        run(&database, create)?;
+        assert!(matches!(
+            run(&database, "create table student(name text);"),
+            Err(BEErrors::DuplicateDefinition(_))
+        ));
+        run(&database, "insert into student (name) values (john);")?;
+        run(
+            &database,
+            "insert into student (age, name) values (25, alice);",
+        )?;
+        assert!(matches!(
+            run(&database, "insert into student values (bob, 22);"),
+            Err(BEErrors::ColumnCountMismatch(3, 2))
+        ));
+        database.flush()?;
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        run(&reopened, create)?;
+        run(
+            &reopened,
+            "create table adults as select name, city from student where age > 20;",
+        )?;
+        assert!(matches!(
+            run(
+                &reopened,
+                "create table broken as select name, grade from student;"
+            ),
+            Err(BEErrors::MissingColumn(_))
+        ));
+        run(&reopened, "insert into student (name) values (carol);")?;
+
+        let rows =
+            select::<(String, i64, String)>(&reopened, "select name, age, city from student;")?;
+        assert_eq!(
+            vec![
+                (String::from("john"), 18, String::from("new york")),
+                (String::from("alice"), 25, String::from("new york")),
+                (String::from("carol"), 18, String::from("new york")),
+            ],
+            rows
+        );
+
+        let rows = select::<(String, String)>(&reopened, "select name, city from adults;")?;
+        assert_eq!(
+            vec![(String::from("alice"), String::from("new york"))],
+            rows
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_page_size(temp_db_path: TempDbPath) -> BEResult<()> {
+        assert!(matches!(
+            OpenOptions::new().page_size(1000).open(&temp_db_path),
+            Err(BEErrors::InvalidPageSize(1000))
+        ));
+        assert!(matches!(
+            OpenOptions::new().page_size(128 * 1024).open(&temp_db_path),
+            Err(BEErrors::InvalidPageSize(_))
+        ));
+
+        let database = OpenOptions::new()
+            .page_size(16 * 1024)
+            .open(&temp_db_path)?;
+        run(&database, "create table essay(title text, body text);")?;
+        // rows this large would be moved to overflow pages with the default page size
+        let body = "a".repeat(3000);
+        for title in ["first", "second"] {
+            run(
+                &database,
+                &format!("insert into essay values ({title}, '{body}');"),
+            )?;
+        }
+        database.flush()?;
+        assert_eq!(
+            16 * 1024,
+            std::fs::metadata(temp_db_path.join("data/essay"))?.len()
+        );
+        assert_eq!(
+            r#"{"format_version":1,"page_size":16384}"#,
+            std::fs::read_to_string(temp_db_path.join("database.json"))?
+        );
+
+        drop(database);
+        let reopened = Database::open(&temp_db_path)?;
+        let rows = query(
+            &reopened,
+            &format!("select title from essay where body = '{body}';"),
+        )?;
+        assert_eq!(2, rows.count());
+        drop(reopened);
+        assert!(matches!(
+            OpenOptions::new().page_size(8192).open(&temp_db_path),
+            Err(BEErrors::PageSizeMismatch(8192, 16384))
+        ));
+        Ok(())
+    }
+}
@@ -1,259 +1,497 @@
-use std::alloc::{alloc, dealloc, Layout};
+use frontend::{command::statement::insert::Value, TableDefinition};
+
+use crate::{
+    errors::{BEErrors, BEResult},
+    vm::expression::evaluate_default,
+};
+
+use super::{checksum::Crc32, record, Field};
+
+// every page starts with its kind followed by two `u32` depending on the kind, for
+// slotted pages the slot count and the offset the record area starts at, for overflow
+// pages the index of the next page in the chain and the length of the chunk held, and
+// the checksum of the page as written to disk
+const HEADER_SIZE: usize = 4 * std::mem::size_of::<u32>();
+const CHECKSUM_OFFSET: usize = 3 * std::mem::size_of::<u32>();
+const SLOT_ENTRY_SIZE: usize = std::mem::size_of::<u32>();
+const PAGE_KIND_SLOTTED: u32 = 1;
+const PAGE_KIND_OVERFLOW: u32 = 2;
+// overflow pages ending a chain
+const NO_NEXT_PAGE: u32 = u32::MAX;
+// Slot entries keep the record offset in the low bits, the schema version the record
+// was written with in the bits above and whether the record was moved to overflow pages
+// in the highest bit.
+const SLOT_OFFSET_BITS: u32 = 16;
+const SLOT_OFFSET_MASK: u32 = (1 << SLOT_OFFSET_BITS) - 1;
+pub(super) const SLOT_OVERFLOW_FLAG: u32 = 1 << (u32::BITS - 1);
+const SLOT_VERSION_MASK: u32 = !SLOT_OVERFLOW_FLAG;
+/// Highest schema version a slot entry can record.
+pub(super) const MAX_SCHEMA_VERSION: usize = (SLOT_VERSION_MASK >> SLOT_OFFSET_BITS) as usize;
+
+/// Page a in-memory storage of rows in table, either a slotted page or an overflow page.
+///
+/// Slotted pages hold rows. The slot array follows the header and grows towards the end
+/// of the page, while records are written from the end of the page towards the front, so
+/// a page takes rows until the two meet. Records are encoded by [`record`].
+///
+/// Pages are all of the page size of their database, which is between
+/// [`super::MIN_PAGE_SIZE`] and [`super::MAX_PAGE_SIZE`] so that record offsets fit a slot
+/// entry. Records larger than [`Page::overflow_threshold`] are split over a chain of
+/// overflow pages of the same table, with the slotted page holding a pointer to the first of them
+/// instead, the `u32` index of that page followed by the varint length of the record.
+#[derive(Debug, Clone)]
+pub(super) struct Page {
+    data: Box<[u8]>,
+    // whether the page changed since it was last read from or written to disk
+    dirty: bool,
+}
 
-use frontend::{command::statement::insert::Value, ColumnName, TableDefinition};
+/// CRC-32 of a page, over all of it but the checksum itself.
+fn checksum(data: &[u8]) -> u32 {
+    Crc32::new()
+        .update(&data[..CHECKSUM_OFFSET])
+        .update(&data[HEADER_SIZE..])
+        .finish()
+}
 
-use crate::errors::{BEErrors, BEResult};
+/// Writes the checksum into the header of a page about to be written to disk.
+pub(crate) fn stamp_checksum(data: &mut [u8]) {
+    let checksum = checksum(data);
+    data[CHECKSUM_OFFSET..HEADER_SIZE].copy_from_slice(&checksum.to_le_bytes());
+}
 
-use super::{Rows, PAGE_SIZE};
+/// Where the record of a slot is kept.
+pub(super) enum StoredRecord<'a> {
+    Inline(&'a [u8]),
+    /// Record of `len` bytes split over the overflow pages starting at `first_page`.
+    Overflow {
+        first_page: usize,
+        len: usize,
+    },
+}
 
-const SLOT_COUNT: usize = 20;
-const SLOT_SIZE: usize = 32;
+impl Page {
+    /// Records larger than this are moved to overflow pages, leaving room for a few
+    /// rows on every slotted page.
+    pub(super) fn overflow_threshold(page_size: usize) -> usize {
+        page_size / 4
+    }
 
-#[derive(Debug)]
-pub struct Slot(u8);
+    /// Most bytes a record of `table_definition` can take up, or `None` when a column
+    /// has no bound.
+    pub(super) fn max_record_size(table_definition: &TableDefinition) -> Option<usize> {
+        table_definition
+            .columns
+            .iter()
+            .map(|it| record::max_field_size(&it.1))
+            .try_fold(0usize, |total, size| Some(total.saturating_add(size?)))
+    }
 
-/// Page a in-memory storage of rows in table.
-/// To allow multiple rows to be present, it will use slot page design.
-/// For simplicity, header of page consists of 20 slots. i.e. a page can have max of 20 rows in it.
-/// First entry in page is slot count which is free to use.
-#[derive(Debug, Clone)]
-pub(super) struct Page {
-    page: *mut u8,
-    free_offset: usize,
-}
+    /// Bytes of a record an overflow page holds.
+    pub(super) fn overflow_capacity(page_size: usize) -> usize {
+        page_size - HEADER_SIZE
+    }
 
-unsafe impl Send for Page {}
-unsafe impl Sync for Page {}
-
-impl Default for Page {
-    fn default() -> Self {
-        let page = unsafe { alloc(Layout::from_size_align_unchecked(PAGE_SIZE, 1)) as *mut u8 };
-        let free_slot = 0_u32;
-        unsafe {
-            std::ptr::copy_nonoverlapping(
-                &free_slot as *const u32 as *const u8,
-                page,
-                std::mem::size_of::<u32>(),
-            )
-        };
-        Self {
-            page,
-            // (free slot num + free offset pos) + 20 slots space are available for table data.
-            free_offset: (SLOT_COUNT * SLOT_SIZE) + 2 * std::mem::size_of::<u32>(),
+    pub(crate) fn new(initialized_data: Vec<u8>, page_size: usize) -> Page {
+        let mut data = initialized_data;
+        data.resize(page_size, 0);
+        Page {
+            data: data.into_boxed_slice(),
+            dirty: false,
         }
     }
-}
 
-impl Drop for Page {
-    fn drop(&mut self) {
-        unsafe {
-            dealloc(self.page, Layout::from_size_align_unchecked(PAGE_SIZE, 1));
-        }
+    /// Whether the page changed since it was read from disk or last flushed. Pages built
+    /// in memory start out changed.
+    pub(super) fn is_dirty(&self) -> bool {
+        self.dirty
     }
-}
 
-impl Page {
-    pub(crate) fn new(initialized_data: Vec<u8>) -> Page {
-        let mut initialized_data = initialized_data;
-        let page = initialized_data.as_mut_ptr();
-        std::mem::forget(initialized_data);
-        let free_offset =
-            unsafe { *(page.add(std::mem::size_of::<u32>() as usize) as *const u32) } as usize;
-
-        Page { page, free_offset }
-    }
-
-    fn get_alignment_padding<T>(offset: usize) -> usize {
-        let required_alignment = std::mem::align_of::<T>();
-        let misalignment = offset % required_alignment;
-        if misalignment > 0 {
-            required_alignment - misalignment
-        } else {
-            0
+    /// Records that the page has to be written again, as the file it was read from or
+    /// last written to is no longer its table's.
+    pub(super) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Records that the page as it is now was written to disk.
+    pub(super) fn mark_flushed(&mut self) {
+        self.dirty = false;
+    }
+
+    /// Whether `data` read from disk is a whole page whose checksum matches.
+    pub(super) fn is_intact(data: &[u8], page_size: usize) -> bool {
+        data.len() == page_size
+            && data[CHECKSUM_OFFSET..HEADER_SIZE] == checksum(data).to_le_bytes()
+    }
+
+    /// Empty slotted page.
+    pub(super) fn slotted(page_size: usize) -> Page {
+        let mut page = Page::new(Vec::new(), page_size);
+        page.write_u32(0, PAGE_KIND_SLOTTED);
+        page.write_u32(8, page_size as u32);
+        page
+    }
+
+    /// Overflow page holding `chunk` of a record, followed by `next` in the chain.
+    pub(super) fn overflow(chunk: &[u8], next: Option<usize>, page_size: usize) -> BEResult<Page> {
+        let next = match next {
+            Some(next) => u32::try_from(next)
+                .ok()
+                .filter(|it| *it != NO_NEXT_PAGE)
+                .ok_or_else(|| {
+                    BEErrors::InternalError(format!("page {next} can not be linked to"))
+                })?,
+            None => NO_NEXT_PAGE,
+        };
+        if chunk.len() > Page::overflow_capacity(page_size) {
+            return Err(BEErrors::InternalError(format!(
+                "chunk of {} bytes does not fit an overflow page",
+                chunk.len()
+            )));
         }
+
+        let mut page = Page::new(Vec::new(), page_size);
+        page.write_u32(0, PAGE_KIND_OVERFLOW);
+        page.write_u32(4, next);
+        page.write_u32(8, chunk.len() as u32);
+        page.data[HEADER_SIZE..HEADER_SIZE + chunk.len()].copy_from_slice(chunk);
+        Ok(page)
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.data[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
     }
 
-    pub fn available_slot_pos(&self) -> Option<u32> {
-        let free_slot_pos = unsafe { *(self.page as *const u32) };
-        if free_slot_pos <= 20 {
-            Some(free_slot_pos)
-        } else {
-            None
+    // every change to a page ends with updating its header through here
+    fn write_u32(&mut self, offset: usize, value: u32) {
+        self.data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        self.dirty = true;
+    }
+
+    pub fn is_slotted(&self) -> bool {
+        self.read_u32(0) == PAGE_KIND_SLOTTED
+    }
+
+    pub fn is_overflow(&self) -> bool {
+        self.read_u32(0) == PAGE_KIND_OVERFLOW
+    }
+
+    /// Index of the page continuing the chain of this overflow page.
+    pub fn overflow_next(&self) -> Option<usize> {
+        Some(self.read_u32(4))
+            .filter(|it| *it != NO_NEXT_PAGE)
+            .map(|it| it as usize)
+    }
+
+    /// Chunk of the record held by this overflow page.
+    pub fn overflow_chunk(&self) -> BEResult<&[u8]> {
+        if !self.is_overflow() {
+            return Err(BEErrors::InternalError(String::from(
+                "page is not an overflow page",
+            )));
         }
+        let len = self.read_u32(8) as usize;
+        self.data
+            .get(HEADER_SIZE..HEADER_SIZE + len)
+            .ok_or_else(|| {
+                BEErrors::InternalError(format!("chunk of {len} bytes overruns the page"))
+            })
     }
 
-    unsafe fn increment_free_slot_pos(&self, new_value: u32) {
-        std::ptr::copy_nonoverlapping(
-            &new_value as *const u32 as *const u8,
-            self.page,
-            std::mem::size_of::<u32>(),
+    /// Points `slot` at `offset`, keeping the rest of its entry, for tests to break a page.
+    #[cfg(test)]
+    pub(super) fn set_slot_offset(&mut self, slot: usize, offset: usize) {
+        let entry = self.read_u32(Page::slot_offset(slot));
+        self.write_u32(
+            Page::slot_offset(slot),
+            (entry & !SLOT_OFFSET_MASK) | offset as u32,
         );
     }
 
-    pub fn get_data(&self) -> *const u8 {
-        self.page
+    fn records_start(&self) -> usize {
+        self.read_u32(8) as usize
     }
 
-    pub fn write(
-        &mut self,
+    fn slot_offset(slot: usize) -> usize {
+        HEADER_SIZE + slot * SLOT_ENTRY_SIZE
+    }
+
+    /// Bytes left between the end of the slot array and the start of the records.
+    pub fn free_space(&self) -> usize {
+        if !self.is_slotted() {
+            return 0;
+        }
+        self.records_start()
+            .min(self.data.len())
+            .saturating_sub(Page::slot_offset(self.row_count()))
+    }
+
+    /// Whether a record of `len` bytes fits, along with the slot entry pointing to it.
+    pub fn fits(&self, len: usize) -> bool {
+        len + SLOT_ENTRY_SIZE <= self.free_space()
+    }
+
+    fn record_bytes(&self, offset: usize) -> BEResult<&[u8]> {
+        self.data.get(offset..).ok_or_else(|| {
+            BEErrors::InternalError(format!("record offset {offset} is outside the page"))
+        })
+    }
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Arranges insert values in the order columns appear in the table definition and
+    /// parses them. Named values may come in any order and may leave out columns having
+    /// a default, which is evaluated in their place, unnamed values are taken
+    /// positionally and must cover every column.
+    fn values_in_column_order(
         values: Vec<Value>,
         table_definition: &TableDefinition,
-    ) -> BEResult<()> {
-        let Some(available_slot_pos) = self.available_slot_pos() else {
-            return Err(BEErrors::InternalError(String::from(
-                "No free space in page",
-            )));
-        };
-
-        unsafe {
-            let ptr = self.page.add(
-                (2 * std::mem::size_of::<u32>()
-                    + available_slot_pos as usize * std::mem::size_of::<u32>())
-                    as usize,
-            );
-            std::ptr::copy_nonoverlapping(
-                &self.free_offset as *const usize as *const u8,
-                ptr,
-                std::mem::size_of::<u32>(),
-            );
-            self.increment_free_slot_pos(available_slot_pos + 1);
+    ) -> BEResult<Vec<Field>> {
+        let columns = &table_definition.columns;
+        let named = values.iter().all(|it| {
+            matches!(
+                it,
+                Value::NamedValue(..) | Value::NamedBlob(..) | Value::NamedPlaceholder(..)
+            )
+        });
+        if values.len() > columns.len() || (!named && values.len() != columns.len()) {
+            return Err(BEErrors::ColumnCountMismatch(columns.len(), values.len()));
         }
 
-        for value in values {
-            match value {
-                Value::NamedValue(name, value) => {
-                    let Some(column) = table_definition.columns.iter().find(|it| it.0 == name)
-                    else {
+        let mut ordered = vec![None; columns.len()];
+        for (position, value) in values.into_iter().enumerate() {
+            let index = match &value {
+                Value::NamedValue(name, _) | Value::NamedBlob(name, _) => {
+                    let Some(index) = columns.iter().position(|it| it.0 == *name) else {
                         return Err(BEErrors::MissingColumn(format!("Column {name} not found")));
                     };
-                    match column.1 {
-                        frontend::ColumnType::Int => {
-                            let Ok(value) = value.parse::<i64>() else {
-                                return Err(BEErrors::MismatchedDataType(
-                                    format!("{name}"),
-                                    "Int",
-                                    format!("{value}"),
-                                ));
-                            };
-
-                            unsafe {
-                                self.free_offset +=
-                                    Page::get_alignment_padding::<i64>(self.free_offset);
-                                let ptr = self.page.add(self.free_offset);
-                                std::ptr::copy_nonoverlapping(
-                                    &value as *const i64 as *const u8,
-                                    ptr,
-                                    std::mem::size_of::<i64>(),
-                                );
-                            }
-                            self.free_offset += std::mem::size_of::<i64>();
-                        }
-                        frontend::ColumnType::Text => unsafe {
-                            //write the length of string
-                            self.free_offset +=
-                                Page::get_alignment_padding::<usize>(self.free_offset);
-                            let ptr = self.page.add(self.free_offset);
-                            let str_len = value.len();
-                            std::ptr::copy_nonoverlapping(
-                                &str_len as *const usize as *const u8,
-                                ptr,
-                                std::mem::size_of::<usize>(),
-                            );
-                            self.free_offset += std::mem::size_of::<usize>();
-
-                            //write actual string
-                            let ptr = self.page.add(self.free_offset);
-                            std::ptr::copy_nonoverlapping(value.as_ptr(), ptr, value.len());
-                            self.free_offset += value.len();
-                        },
-                    };
+                    index
                 }
-                Value::UnnamedValue(_) => todo!("not yet implemented"),
+                Value::UnnamedValue(_) | Value::UnnamedBlob(_) => position,
+                Value::NamedPlaceholder(_, placeholder)
+                | Value::UnnamedPlaceholder(placeholder) => {
+                    return Err(BEErrors::UnboundParameter(placeholder.to_string()));
+                }
+            };
+            if ordered[index].is_some() {
+                return Err(BEErrors::DuplicateColumn(columns[index].0.clone()));
             }
+            ordered[index] = Some(value);
         }
 
-        let free_offset = self.free_offset as u32;
-        unsafe {
-            std::ptr::copy_nonoverlapping(
-                &free_offset as *const u32 as *const u8,
-                self.page.add(std::mem::size_of::<u32>() as usize),
-                std::mem::size_of::<u32>(),
-            )
-        };
+        ordered
+            .into_iter()
+            .zip(columns.iter())
+            .map(|(value, column)| match value {
+                Some(Value::NamedBlob(_, bytes) | Value::UnnamedBlob(bytes)) => {
+                    Field::from_bytes(column, bytes)
+                }
+                Some(value) => Field::parse(column, value.value()),
+                None => evaluate_default(column)?.ok_or_else(|| {
+                    BEErrors::MissingColumn(format!("No value for column {}", column.0))
+                }),
+            })
+            .collect()
+    }
+
+    /// Validates insert values against the table definition and parses them
+    /// into the representation they are stored with, without touching the page.
+    pub fn encode(values: Vec<Value>, table_definition: &TableDefinition) -> BEResult<Vec<Field>> {
+        Page::values_in_column_order(values, table_definition)
+    }
+
+    /// Writes a record encoded by [`record::encode`] into a new slot, tagged with the
+    /// schema `version` its fields are laid out in. Callers check [`Page::fits`] first.
+    pub fn write_record(&mut self, bytes: &[u8], version: usize) -> BEResult<()> {
+        self.write_slot(bytes, version, false)
+    }
+
+    /// Writes a slot pointing to a record of `len` bytes kept in the chain of overflow
+    /// pages starting at `first_page`.
+    pub fn write_overflow_pointer(
+        &mut self,
+        first_page: usize,
+        len: usize,
+        version: usize,
+    ) -> BEResult<()> {
+        let first_page = u32::try_from(first_page).map_err(|_| {
+            BEErrors::InternalError(format!("page {first_page} can not be pointed to"))
+        })?;
+        let mut bytes = first_page.to_le_bytes().to_vec();
+        record::write_varint(&mut bytes, len as u64);
+        self.write_slot(&bytes, version, true)
+    }
+
+    fn write_slot(&mut self, bytes: &[u8], version: usize, overflow: bool) -> BEResult<()> {
+        if !self.fits(bytes.len()) {
+            return Err(BEErrors::InternalError(format!(
+                "record of {} bytes does not fit the {} bytes free in page",
+                bytes.len(),
+                self.free_space()
+            )));
+        }
+        if version > MAX_SCHEMA_VERSION {
+            return Err(BEErrors::InternalError(format!(
+                "schema version {version} can not be stored in page"
+            )));
+        }
+
+        let slot = self.row_count();
+        let offset = self.records_start().min(self.data.len()) - bytes.len();
+        self.data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        let flag = if overflow { SLOT_OVERFLOW_FLAG } else { 0 };
+        self.write_u32(
+            Page::slot_offset(slot),
+            flag | ((version as u32) << SLOT_OFFSET_BITS) | offset as u32,
+        );
+        self.write_u32(4, slot as u32 + 1);
+        self.write_u32(8, offset as u32);
 
         Ok(())
     }
 
-    pub fn read_all_rows(
-        &self,
-        columns: &[ColumnName],
-        table_definition: &TableDefinition,
-    ) -> BEResult<Option<Rows>> {
-        let written_slots_count = unsafe { *(self.page as *const u32) };
-        if written_slots_count <= 0 {
-            return Ok(None);
+    /// Number of rows written to this page, none for overflow pages.
+    pub fn row_count(&self) -> usize {
+        if !self.is_slotted() {
+            return 0;
+        }
+        self.read_u32(4) as usize
+    }
+
+    /// Problems with the layout of the page, along with the slot they concern. The
+    /// records themselves are not decoded.
+    pub fn check_layout(&self) -> Vec<(Option<usize>, String)> {
+        let page_size = self.data.len();
+        if self.is_overflow() {
+            let len = self.read_u32(8) as usize;
+            if len > Page::overflow_capacity(page_size) {
+                return vec![(None, format!("chunk of {len} bytes overruns the page"))];
+            }
+            return Vec::new();
+        }
+        if !self.is_slotted() {
+            return vec![(None, format!("unknown page kind {}", self.read_u32(0)))];
+        }
+
+        let row_count = self.row_count();
+        let slots_end = Page::slot_offset(row_count);
+        if slots_end > page_size {
+            return vec![(None, format!("{row_count} slots overrun the page"))];
+        }
+        let records_start = self.records_start();
+        if records_start < slots_end || records_start > page_size {
+            return vec![(
+                None,
+                format!(
+                    "record area starting at {records_start} overlaps the {row_count} slots \
+                    or the end of the page"
+                ),
+            )];
+        }
+        (0..row_count)
+            .filter_map(|slot| {
+                let offset = (self.read_u32(Page::slot_offset(slot)) & SLOT_OFFSET_MASK) as usize;
+                (offset < records_start || offset >= page_size).then(|| {
+                    (
+                        Some(slot),
+                        format!(
+                            "record offset {offset} is outside the record area \
+                            {records_start}..{page_size}"
+                        ),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Looks up the record stored in `slot` along with the schema version it was
+    /// written with.
+    pub fn stored_record(&self, slot: usize) -> BEResult<(StoredRecord<'_>, usize)> {
+        if slot >= self.row_count() {
+            return Err(BEErrors::InternalError(format!(
+                "slot {slot} is not written in page"
+            )));
         }
-        let mut rows = Vec::new();
-        let mut offset = 2 * std::mem::size_of::<u32>();
-        for _ in 0..written_slots_count {
-            let data_offset = unsafe { *(self.page.add(offset) as *const u32) } as usize;
-            let row = self.read(data_offset, columns, table_definition)?;
-            offset += std::mem::size_of::<u32>();
-            rows.push(row);
+        if Page::slot_offset(slot + 1) > self.data.len() {
+            return Err(BEErrors::InternalError(format!(
+                "slot {slot} is outside the page"
+            )));
         }
+        let slot_entry = self.read_u32(Page::slot_offset(slot));
+        let data_offset = (slot_entry & SLOT_OFFSET_MASK) as usize;
+        let version = ((slot_entry & SLOT_VERSION_MASK) >> SLOT_OFFSET_BITS) as usize;
+        let bytes = self.record_bytes(data_offset)?;
 
-        Ok(Some(rows))
+        if slot_entry & SLOT_OVERFLOW_FLAG == 0 {
+            return Ok((StoredRecord::Inline(bytes), version));
+        }
+        let mut reader = record::Reader { bytes, offset: 0 };
+        let first_page = u32::from_le_bytes(reader.read_array()?) as usize;
+        let len = usize::try_from(reader.read_varint()?)
+            .map_err(|_| BEErrors::InternalError(format!("record in slot {slot} is too large")))?;
+        Ok((StoredRecord::Overflow { first_page, len }, version))
     }
 
-    pub fn read(
-        &self,
-        offset: usize,
-        columns: &[ColumnName],
+    /// Decodes a record written under schema `version` with one field per column of the
+    /// table definition. Records written under an older schema version are mapped to the
+    /// current columns, with columns added since then taking their default.
+    pub fn decode_record(
+        bytes: &[u8],
+        version: usize,
         table_definition: &TableDefinition,
-    ) -> BEResult<Vec<Value>> {
-        let mut offset = offset;
-        let mut result = Vec::with_capacity(columns.len());
-        for ColumnName(name) in columns {
-            let Some(column) = table_definition.columns.iter().find(|it| *it.0 == *name) else {
-                return Err(BEErrors::MissingColumn(format!("Column {name} not found")));
-            };
+    ) -> BEResult<Vec<Field>> {
+        if version == table_definition.version() {
+            let column_types = table_definition.columns.iter().map(|it| &it.1);
+            return record::decode(bytes, column_types);
+        }
+        let Some(layout) = table_definition.schema_history.get(version) else {
+            return Err(BEErrors::InternalError(format!(
+                "record has unknown schema version {version}"
+            )));
+        };
 
-            match column.1 {
-                frontend::ColumnType::Int => unsafe {
-                    offset += Page::get_alignment_padding::<i64>(offset);
-                    let ptr = self.page.add(offset);
-                    let value = *(ptr as *const i64);
-                    result.push(Value::NamedValue(name.to_string(), value.to_string()));
-                    offset += std::mem::size_of::<i64>();
-                },
-                frontend::ColumnType::Text => unsafe {
-                    offset += Page::get_alignment_padding::<usize>(offset);
-                    let ptr = self.page.add(offset);
-                    let str_len = *(ptr as *const usize);
-                    offset += std::mem::size_of::<usize>();
-
-                    let ptr = self.page.add(offset);
-                    let byte_slice = std::slice::from_raw_parts(ptr, str_len);
-                    let string_value = std::str::from_utf8_unchecked(byte_slice);
-                    offset += str_len;
-                    result.push(Value::NamedValue(
-                        name.to_string(),
-                        string_value.to_string(),
-                    ));
-                },
+        let stored = record::decode(bytes, layout.0.iter().map(|it| &it.0))?;
+        let mut record = vec![None; table_definition.columns.len()];
+        for (field, (_, index)) in stored.into_iter().zip(layout.0.iter()) {
+            if let Some(index) = index {
+                record[*index] = Some(field);
             }
         }
-
-        Ok(result)
+        record
+            .into_iter()
+            .zip(table_definition.columns.iter())
+            .map(|(field, column)| match field {
+                Some(field) => Ok(field),
+                None => evaluate_default(column)?.ok_or_else(|| {
+                    BEErrors::MissingColumn(format!("No value for column {}", column.0))
+                }),
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::str::FromStr;
+
     use super::*;
-    use crate::fixtures::student_table_fixture;
+    use crate::{
+        database::{DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE},
+        fixtures::student_table_fixture,
+    };
     use rstest::rstest;
 
+    fn read_record(page: &Page, slot: usize, table: &TableDefinition) -> BEResult<Vec<Field>> {
+        let (StoredRecord::Inline(bytes), version) = page.stored_record(slot)? else {
+            panic!("record should be kept in the page");
+        };
+        Page::decode_record(bytes, version, table)
+    }
+
     #[test]
     fn test_write() -> BEResult<()> {
         Ok(())
@@ -261,24 +499,96 @@ mod test {
 
     #[rstest]
     fn test_read_after_write(student_table_fixture: TableDefinition) -> BEResult<()> {
-        let mut page = Page::default();
-        page.write(
+        let mut page = Page::slotted(DEFAULT_PAGE_SIZE);
+        let fields = Page::encode(
             vec![
                 Value::NamedValue(String::from("name"), String::from("student1")),
                 Value::NamedValue(String::from("age"), String::from("22")),
             ],
             &student_table_fixture,
         )?;
+        page.write_record(&record::encode(&fields)?, student_table_fixture.version())?;
 
-        let columns = page.read(
-            0,
-            &vec![
-                ColumnName(String::from("name")),
-                ColumnName(String::from("age")),
-            ],
-            &student_table_fixture,
-        )?;
-        println!("{columns:?}");
+        assert_eq!(1, page.row_count());
+        let record = read_record(&page, 0, &student_table_fixture)?;
+        assert_eq!(
+            vec![Field::Text(String::from("student1")), Field::Int(22)],
+            record
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_is_checked_to_be_utf8() -> BEResult<()> {
+        let mut page = Page::slotted(DEFAULT_PAGE_SIZE);
+        page.write_record(&record::encode(&[Field::Blob(vec![0xca, 0xfe])])?, 0)?;
+
+        let blob_table = TableDefinition::from_str("create table blob (data blob);")?;
+        assert_eq!(
+            vec![Field::Blob(vec![0xca, 0xfe])],
+            read_record(&page, 0, &blob_table)?
+        );
+        let text_table = TableDefinition::from_str("create table text (data text);")?;
+        assert!(matches!(
+            read_record(&page, 0, &text_table),
+            Err(BEErrors::InternalError(_))
+        ));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(DEFAULT_PAGE_SIZE)]
+    #[case(MAX_PAGE_SIZE)]
+    fn test_slots_and_records_grow_towards_each_other(#[case] page_size: usize) -> BEResult<()> {
+        let table = TableDefinition::from_str("create table numbers (value int);")?;
+        let mut page = Page::slotted(page_size);
+        assert_eq!(page_size - HEADER_SIZE, page.free_space());
+
+        // each row takes 8 bytes of record and 4 of slot entry
+        let rows = (page_size - HEADER_SIZE) / 12;
+        for value in 0..rows {
+            let bytes = record::encode(&[Field::Int(value as i64)])?;
+            assert!(page.fits(bytes.len()));
+            page.write_record(&bytes, table.version())?;
+        }
+        assert!(rows > 20);
+        assert_eq!(rows, page.row_count());
+        assert!(!page.fits(8));
+        assert!(matches!(
+            page.write_record(&record::encode(&[Field::Int(0)])?, table.version()),
+            Err(BEErrors::InternalError(_))
+        ));
+
+        for slot in [0, rows / 2, rows - 1] {
+            assert_eq!(
+                vec![Field::Int(slot as i64)],
+                read_record(&page, slot, &table)?
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow_page() -> BEResult<()> {
+        let chunk = vec![7; Page::overflow_capacity(DEFAULT_PAGE_SIZE)];
+        let page = Page::overflow(&chunk, Some(3), DEFAULT_PAGE_SIZE)?;
+        assert!(page.is_overflow());
+        assert_eq!(0, page.row_count());
+        assert_eq!(0, page.free_space());
+        assert_eq!(Some(3), page.overflow_next());
+        assert_eq!(&chunk[..], page.overflow_chunk()?);
+
+        let last = Page::overflow(&chunk[..10], None, DEFAULT_PAGE_SIZE)?;
+        assert_eq!(None, last.overflow_next());
+        assert_eq!(&chunk[..10], last.overflow_chunk()?);
+        assert!(Page::overflow(&[0; DEFAULT_PAGE_SIZE], None, DEFAULT_PAGE_SIZE).is_err());
+
+        let mut page = Page::slotted(DEFAULT_PAGE_SIZE);
+        page.write_overflow_pointer(5, 100_000, 2)?;
+        let (StoredRecord::Overflow { first_page, len }, version) = page.stored_record(0)? else {
+            panic!("record should be kept in overflow pages");
+        };
+        assert_eq!((5, 100_000, 2), (first_page, len, version));
         Ok(())
     }
 }
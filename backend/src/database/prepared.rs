@@ -0,0 +1,498 @@
+use frontend::{
+    command::statement::{
+        expression::Expression,
+        insert::{Placeholder, Value},
+        StatementCommand,
+    },
+    Column, ColumnType, TableDefinition,
+};
+
+use crate::{
+    errors::{BEErrors, BEResult},
+    vm::ExecutionResult,
+};
+
+use super::{field::type_name, Database};
+
+pub use frontend::command::statement::insert::Parameter;
+
+/// Rejects `parameter` when bound where `column` is expected and its type does not fit
+/// the column. Ints also fit real and decimal columns and reals decimal ones, text fits
+/// every column written as text, including temporal and decimal ones.
+fn check_column_type(parameter: &Parameter, column: &Column) -> BEResult<()> {
+    let fits = match parameter {
+        Parameter::Int(_) => matches!(
+            column.1,
+            ColumnType::Int | ColumnType::Real | ColumnType::Decimal(..)
+        ),
+        Parameter::Real(_) => matches!(column.1, ColumnType::Real | ColumnType::Decimal(..)),
+        Parameter::Text(_) => matches!(
+            column.1,
+            ColumnType::Text
+                | ColumnType::Varchar(_)
+                | ColumnType::Char(_)
+                | ColumnType::Date
+                | ColumnType::Time
+                | ColumnType::Timestamp
+                | ColumnType::Decimal(..)
+        ),
+        Parameter::Bool(_) => column.1 == ColumnType::Bool,
+        Parameter::Blob(_) => column.1 == ColumnType::Blob,
+    };
+    if fits {
+        return Ok(());
+    }
+    Err(BEErrors::MismatchedDataType(
+        column.0.clone(),
+        type_name(&column.1),
+        parameter.to_string(),
+    ))
+}
+
+/// What a placeholder is used as, which decides the parameters it accepts.
+#[derive(Debug, Clone)]
+enum Usage {
+    /// Inserted into or compared with a column.
+    Column(Column),
+    /// Operand of arithmetic on numbers, or compared with a number.
+    Number,
+    /// Read as text, like function arguments or operands next to temporal values.
+    Text,
+    /// Evaluated as a condition.
+    Condition,
+}
+
+impl Usage {
+    fn check(&self, number: usize, parameter: &Parameter) -> BEResult<()> {
+        let (fits, expected) = match self {
+            Usage::Column(column) => return check_column_type(parameter, column),
+            Usage::Number => (
+                matches!(parameter, Parameter::Int(_) | Parameter::Real(_)),
+                "Number",
+            ),
+            Usage::Text => (matches!(parameter, Parameter::Text(_)), "Text"),
+            Usage::Condition => (matches!(parameter, Parameter::Bool(_)), "Bool"),
+        };
+        if fits {
+            return Ok(());
+        }
+        Err(BEErrors::MismatchedParameterType(
+            number,
+            expected,
+            parameter.to_string(),
+        ))
+    }
+
+    fn is_temporal(&self) -> bool {
+        match self {
+            Usage::Column(column) => matches!(
+                column.1,
+                ColumnType::Date | ColumnType::Time | ColumnType::Timestamp
+            ),
+            Usage::Text => true,
+            Usage::Number | Usage::Condition => false,
+        }
+    }
+}
+
+/// Statement parsed once by [`Database::prepare`] and executed any number of times
+/// with different parameters.
+/// `?` placeholders are numbered in the order they appear, `$n` placeholders refer to
+/// the n-th parameter directly.
+#[derive(Debug, Clone)]
+pub struct PreparedStatement {
+    database: Database,
+    command: StatementCommand,
+    parameter_count: usize,
+}
+
+impl PreparedStatement {
+    pub(super) fn new(database: Database, command: StatementCommand) -> BEResult<Self> {
+        let mut command = command;
+        let mut next = 0;
+        let mut parameter_count = 0;
+        for mut slot in placeholder_slots(&mut command) {
+            let Some(placeholder) = slot.placeholder_mut() else {
+                continue;
+            };
+            let number = match *placeholder {
+                Placeholder::Next => {
+                    next += 1;
+                    next
+                }
+                Placeholder::Numbered(0) => {
+                    return Err(BEErrors::UnboundParameter(placeholder.to_string()))
+                }
+                Placeholder::Numbered(number) => number,
+            };
+            *placeholder = Placeholder::Numbered(number);
+            parameter_count = parameter_count.max(number);
+        }
+
+        Ok(PreparedStatement {
+            database,
+            command,
+            parameter_count,
+        })
+    }
+
+    /// Number of parameters `execute` expects.
+    pub fn parameter_count(&self) -> usize {
+        self.parameter_count
+    }
+
+    /// Binds `parameters` to the placeholders and runs the statement.
+    pub fn execute(&self, parameters: &[Parameter]) -> BEResult<ExecutionResult> {
+        if parameters.len() != self.parameter_count {
+            return Err(BEErrors::ParameterCountMismatch(
+                self.parameter_count,
+                parameters.len(),
+            ));
+        }
+
+        for (number, usage) in self.placeholder_usages()? {
+            usage.check(number, &parameters[number - 1])?;
+        }
+
+        let mut command = self.command.clone();
+        for mut slot in placeholder_slots(&mut command) {
+            if let Some(Placeholder::Numbered(number)) = slot.placeholder_mut() {
+                let parameter = &parameters[*number - 1];
+                slot.bind(parameter);
+            }
+        }
+
+        self.database.execute(command)
+    }
+
+    /// What the placeholders are used as, either as inserted values or wherever they
+    /// appear in a filter, with columns looked up anew as the table may be altered.
+    fn placeholder_usages(&self) -> BEResult<Vec<(usize, Usage)>> {
+        let (table_name, filter) = match &self.command {
+            StatementCommand::Insert(statement) => {
+                let definition = self.database.get_table_definition(&statement.0)?;
+                let mut bound = Vec::new();
+                for row in &statement.1 {
+                    for (position, value) in row.iter().enumerate() {
+                        let (column, placeholder) = match value {
+                            Value::NamedPlaceholder(name, placeholder) => (
+                                definition.columns.iter().find(|it| it.0 == *name),
+                                placeholder,
+                            ),
+                            Value::UnnamedPlaceholder(placeholder) => {
+                                (definition.columns.get(position), placeholder)
+                            }
+                            _ => continue,
+                        };
+                        if let (Some(column), Placeholder::Numbered(number)) = (column, placeholder)
+                        {
+                            bound.push((*number, Usage::Column(column.clone())));
+                        }
+                    }
+                }
+                return Ok(bound);
+            }
+            StatementCommand::Select(statement) => (&statement.0, &statement.2),
+            StatementCommand::InsertSelect(statement) => (&statement.2 .0, &statement.2 .2),
+            StatementCommand::CreateAs(statement) => (&statement.2 .0, &statement.2 .2),
+            _ => return Ok(Vec::new()),
+        };
+        let mut bound = Vec::new();
+        if let Some(filter) = filter {
+            let definition = self.database.get_table_definition(table_name)?;
+            filter_usages(filter, Some(Usage::Condition), &definition, &mut bound);
+        }
+        Ok(bound)
+    }
+}
+
+/// Collects the usage of every placeholder in `expression`, which itself is used as
+/// `usage`. Placeholders whose use says nothing about their type, like both sides of
+/// `? = ?`, are left out.
+fn filter_usages(
+    expression: &Expression,
+    usage: Option<Usage>,
+    definition: &TableDefinition,
+    usages: &mut Vec<(usize, Usage)>,
+) {
+    match expression {
+        Expression::Placeholder(Placeholder::Numbered(number)) => {
+            if let Some(usage) = usage {
+                usages.push((*number, usage));
+            }
+        }
+        Expression::Comparison(left, _, right) => {
+            filter_usages(left, usage_of(right, definition), definition, usages);
+            filter_usages(right, usage_of(left, definition), definition, usages);
+        }
+        Expression::Arithmetic(left, _, right) => {
+            let operand_usage = |other: &Expression| match usage_of(other, definition) {
+                Some(usage) if usage.is_temporal() => Usage::Text,
+                _ => Usage::Number,
+            };
+            filter_usages(left, Some(operand_usage(right)), definition, usages);
+            filter_usages(right, Some(operand_usage(left)), definition, usages);
+        }
+        Expression::And(left, right) | Expression::Or(left, right) => {
+            filter_usages(left, Some(Usage::Condition), definition, usages);
+            filter_usages(right, Some(Usage::Condition), definition, usages);
+        }
+        Expression::Not(inner) => filter_usages(inner, Some(Usage::Condition), definition, usages),
+        Expression::Function(_, arguments) => {
+            for argument in arguments {
+                filter_usages(argument, Some(Usage::Text), definition, usages);
+            }
+        }
+        Expression::Column(_)
+        | Expression::Literal(_)
+        | Expression::Blob(_)
+        | Expression::Bound(_)
+        | Expression::Placeholder(_)
+        | Expression::Interval(_) => {}
+    }
+}
+
+/// What `expression` evaluates to, as far as it tells a placeholder next to it.
+fn usage_of(expression: &Expression, definition: &TableDefinition) -> Option<Usage> {
+    match expression {
+        Expression::Column(name) => definition
+            .columns
+            .iter()
+            .find(|it| it.0 == name.0)
+            .map(|column| Usage::Column(column.clone())),
+        Expression::Arithmetic(left, _, right) => {
+            let temporal = [left, right]
+                .into_iter()
+                .filter_map(|it| usage_of(it, definition))
+                .any(|it| it.is_temporal());
+            Some(if temporal { Usage::Text } else { Usage::Number })
+        }
+        Expression::Interval(_) => Some(Usage::Text),
+        Expression::Function(name, _) if name == "date_part" => Some(Usage::Number),
+        Expression::Function(..) => Some(Usage::Text),
+        Expression::Comparison(..)
+        | Expression::And(..)
+        | Expression::Or(..)
+        | Expression::Not(_) => Some(Usage::Condition),
+        Expression::Literal(_)
+        | Expression::Blob(_)
+        | Expression::Bound(_)
+        | Expression::Placeholder(_) => None,
+    }
+}
+
+/// Place in a statement which may hold a placeholder.
+enum PlaceholderSlot<'a> {
+    Value(&'a mut Value),
+    Expression(&'a mut Expression),
+}
+
+impl PlaceholderSlot<'_> {
+    fn placeholder_mut(&mut self) -> Option<&mut Placeholder> {
+        match self {
+            PlaceholderSlot::Value(
+                Value::NamedPlaceholder(_, placeholder) | Value::UnnamedPlaceholder(placeholder),
+            ) => Some(placeholder),
+            PlaceholderSlot::Expression(Expression::Placeholder(placeholder)) => Some(placeholder),
+            _ => None,
+        }
+    }
+
+    /// Replaces the placeholder with `parameter`. Inserted values hold blobs as their
+    /// bytes and everything else as the text it is written as, which the column parses,
+    /// while filters keep the parameter with its type.
+    fn bind(&mut self, parameter: &Parameter) {
+        match self {
+            PlaceholderSlot::Value(value) => {
+                let name = match value {
+                    Value::NamedValue(name, _)
+                    | Value::NamedBlob(name, _)
+                    | Value::NamedPlaceholder(name, _) => Some(name.clone()),
+                    Value::UnnamedValue(_)
+                    | Value::UnnamedBlob(_)
+                    | Value::UnnamedPlaceholder(_) => None,
+                };
+                **value = match (name, parameter) {
+                    (Some(name), Parameter::Blob(bytes)) => Value::NamedBlob(name, bytes.clone()),
+                    (Some(name), parameter) => Value::NamedValue(name, parameter.to_string()),
+                    (None, Parameter::Blob(bytes)) => Value::UnnamedBlob(bytes.clone()),
+                    (None, parameter) => Value::UnnamedValue(parameter.to_string()),
+                }
+            }
+            PlaceholderSlot::Expression(expression) => {
+                **expression = Expression::Bound(parameter.clone())
+            }
+        }
+    }
+}
+
+/// All places of a statement which may hold placeholders, in the order they appear in the statement.
+fn placeholder_slots(command: &mut StatementCommand) -> Vec<PlaceholderSlot<'_>> {
+    let filter = match command {
+        StatementCommand::Insert(statement) => {
+            return statement
+                .1
+                .iter_mut()
+                .flatten()
+                .map(PlaceholderSlot::Value)
+                .collect();
+        }
+        StatementCommand::Select(statement) => &mut statement.2,
+        StatementCommand::InsertSelect(statement) => &mut statement.2 .2,
+        StatementCommand::CreateAs(statement) => &mut statement.2 .2,
+        StatementCommand::Create(_)
+        | StatementCommand::Drop(_)
+        | StatementCommand::Truncate(_)
+        | StatementCommand::Alter(_)
+        | StatementCommand::Vacuum(_) => return Vec::new(),
+    };
+    filter
+        .iter_mut()
+        .flat_map(Expression::placeholders_mut)
+        .map(PlaceholderSlot::Expression)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::fixtures::{run, select, student_database, temp_database, TempDatabase};
+
+    #[rstest]
+    fn test_prepared_insert(#[from(student_database)] database: TempDatabase) -> BEResult<()> {
+        let insert = database.prepare("insert into student (name, id) values ($2, ?);")?;
+        assert_eq!(2, insert.parameter_count());
+        insert.execute(&[1.into(), "john, the first".into()])?;
+        insert.execute(&[2.into(), "?".into()])?;
+        assert!(matches!(
+            insert.execute(&[3.into()]),
+            Err(BEErrors::ParameterCountMismatch(2, 1))
+        ));
+        assert!(matches!(
+            insert.execute(&["three".into(), "x".into()]),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        assert!(matches!(
+            run(&database, "insert into student (id, name) values (?, ?);"),
+            Err(BEErrors::UnboundParameter(_))
+        ));
+
+        let students = select::<(i64, String)>(&database, "select id, name from student;")?;
+        assert_eq!(
+            vec![(1, String::from("john, the first")), (2, String::from("?"))],
+            students
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_parameters_are_typed(#[from(temp_database)] database: TempDatabase) -> BEResult<()> {
+        run(
+            &database,
+            "create table reading(id int, label text, data blob, value real);",
+        )?;
+
+        let insert = database.prepare("insert into reading values (?, ?, ?, ?);")?;
+        for parameters in [
+            ["10".into(), "a".into(), vec![1].into(), 1.5.into()],
+            [1.into(), 2.into(), vec![1].into(), 1.5.into()],
+            [
+                1.into(),
+                vec![0xca, 0xfe].into(),
+                vec![1].into(),
+                1.5.into(),
+            ],
+            [1.into(), "a".into(), "cafe".into(), 1.5.into()],
+            [1.into(), "a".into(), vec![1].into(), true.into()],
+        ] {
+            assert!(matches!(
+                insert.execute(&parameters),
+                Err(BEErrors::MismatchedDataType(..))
+            ));
+        }
+        // ints are promoted to reals
+        insert.execute(&[1.into(), "10".into(), vec![1].into(), 2.into()])?;
+
+        let select =
+            database.prepare("select label from reading where id = ? and not value < $2;")?;
+        assert!(matches!(
+            select.execute(&["1".into(), 1.0.into()]),
+            Err(BEErrors::MismatchedDataType(column, "Int", _)) if column == "id"
+        ));
+        let ExecutionResult::SelectResult(rows) = select.execute(&[1.into(), 1.0.into()])? else {
+            panic!("select should return rows");
+        };
+        assert_eq!(
+            vec![(String::from("10"),)],
+            rows.deserialize().collect::<BEResult<Vec<_>>>()?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_parameters_are_typed_in_every_position(
+        #[from(temp_database)] database: TempDatabase,
+    ) -> BEResult<()> {
+        run(
+            &database,
+            "create table event(id int, name text, at timestamp, done bool);",
+        )?;
+        run(
+            &database,
+            "insert into event values (1, '10', '2024-05-01 10:00:00', true), \
+             (2, '20', '2023-01-01 00:00:00', false);",
+        )?;
+
+        let arithmetic = database.prepare("select name from event where id + ? = 2;")?;
+        assert!(matches!(
+            arithmetic.execute(&["1".into()]),
+            Err(BEErrors::MismatchedParameterType(1, "Number", _))
+        ));
+        assert_eq!(
+            vec![(String::from("10"),)],
+            rows(arithmetic.execute(&[1.into()])?)?
+        );
+
+        let function = database.prepare(
+            "select name from event where date_part(?, at) = ? and at > ? - interval '1 day';",
+        )?;
+        assert!(matches!(
+            function.execute(&[1.into(), 2024.into(), "2024-01-01".into()]),
+            Err(BEErrors::MismatchedParameterType(1, "Text", _))
+        ));
+        assert!(matches!(
+            function.execute(&["year".into(), "2024".into(), "2024-01-01".into()]),
+            Err(BEErrors::MismatchedParameterType(2, "Number", _))
+        ));
+        assert_eq!(
+            vec![(String::from("10"),)],
+            rows(function.execute(&["year".into(), 2024.into(), "2024-01-02".into()])?)?
+        );
+
+        // bound text stays text, it is not read as the number it spells
+        let nested =
+            database.prepare("select id from event where not (? or done) and name = ?;")?;
+        assert!(matches!(
+            nested.execute(&[0.into(), "20".into()]),
+            Err(BEErrors::MismatchedParameterType(1, "Bool", _))
+        ));
+        assert!(matches!(
+            nested.execute(&[false.into(), 20.into()]),
+            Err(BEErrors::MismatchedDataType(column, "Text", _)) if column == "name"
+        ));
+        assert_eq!(
+            vec![(2,)],
+            rows(nested.execute(&[false.into(), "20".into()])?)?
+        );
+        Ok(())
+    }
+
+    fn rows<T: serde::de::DeserializeOwned>(result: ExecutionResult) -> BEResult<Vec<T>> {
+        let ExecutionResult::SelectResult(rows) = result else {
+            panic!("select should return rows");
+        };
+        rows.deserialize().collect()
+    }
+}
@@ -0,0 +1,264 @@
+//! Encoding of records, shared by writing and reading pages.
+//!
+//! Records are the fields of a row one after another without any padding, each in a
+//! fixed little-endian form for its column type:
+//!
+//! | column type                     | encoding                                        |
+//! |---------------------------------|-------------------------------------------------|
+//! | `int`, `time`, `timestamp`      | 8 byte signed integer, micros for times          |
+//! | `real`                          | 8 byte IEEE 754 double                          |
+//! | `bool`                          | 1 byte, 0 or 1                                  |
+//! | `date`                          | 4 byte signed day number, 0001-01-01 being 1     |
+//! | `decimal`                       | 16 byte signed unscaled value, scale from column |
+//! | `text`, `varchar`, `char`, `blob` | varint byte length followed by the bytes       |
+//!
+//! Varints are unsigned LEB128, 7 bits per byte starting with the lowest, the high bit
+//! set on every byte but the last.
+
+use frontend::ColumnType;
+
+use crate::errors::{BEErrors, BEResult};
+
+use super::{Decimal, Field};
+
+/// Version of the record and page encoding written by this build, recorded in the
+/// database metadata. Version 0 are files written before the encoding was fixed, in the
+/// native byte order and alignment of the machine writing them.
+pub(crate) const RECORD_FORMAT_VERSION: u32 = 1;
+
+/// Encodes `fields` in the order given.
+pub(super) fn encode(fields: &[Field]) -> BEResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for field in fields {
+        match field {
+            Field::Int(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+            Field::Real(value) => bytes.extend_from_slice(&value.to_le_bytes()),
+            Field::Bool(value) => bytes.push(*value as u8),
+            Field::Date(value) => {
+                bytes.extend_from_slice(&Field::date_to_days(*value).to_le_bytes())
+            }
+            Field::Time(value) => {
+                bytes.extend_from_slice(&Field::time_to_micros(*value).to_le_bytes())
+            }
+            Field::Timestamp(value) => {
+                bytes.extend_from_slice(&Field::timestamp_to_micros(*value).to_le_bytes())
+            }
+            Field::Decimal(value) => bytes.extend_from_slice(&value.value.to_le_bytes()),
+            Field::Text(value) => write_length_prefixed(&mut bytes, value.as_bytes()),
+            Field::Blob(value) => write_length_prefixed(&mut bytes, value),
+            Field::Interval(value) => {
+                return Err(BEErrors::InternalError(format!(
+                    "interval {value} can not be stored"
+                )))
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decodes one field per column type from the start of `bytes`.
+pub(super) fn decode<'a>(
+    bytes: &[u8],
+    column_types: impl Iterator<Item = &'a ColumnType>,
+) -> BEResult<Vec<Field>> {
+    let mut reader = Reader { bytes, offset: 0 };
+    let mut record = Vec::new();
+    for column_type in column_types {
+        let field = match column_type {
+            ColumnType::Int => Field::Int(i64::from_le_bytes(reader.read_array()?)),
+            ColumnType::Real => Field::Real(f64::from_le_bytes(reader.read_array()?)),
+            ColumnType::Bool => Field::Bool(reader.read_array::<1>()?[0] != 0),
+            ColumnType::Date => Field::Date(Field::date_from_days(i32::from_le_bytes(
+                reader.read_array()?,
+            ))?),
+            ColumnType::Time => Field::Time(Field::time_from_micros(i64::from_le_bytes(
+                reader.read_array()?,
+            ))?),
+            ColumnType::Timestamp => Field::Timestamp(Field::timestamp_from_micros(
+                i64::from_le_bytes(reader.read_array()?),
+            )?),
+            ColumnType::Decimal(_, scale) => Field::Decimal(Decimal {
+                value: i128::from_le_bytes(reader.read_array()?),
+                scale: *scale,
+            }),
+            ColumnType::Text | ColumnType::Varchar(_) | ColumnType::Char(_) => {
+                let bytes = reader.read_length_prefixed()?;
+                let text = String::from_utf8(bytes.to_vec()).map_err(|err| {
+                    BEErrors::InternalError(format!("text in page is not utf-8, {err}"))
+                })?;
+                Field::Text(text)
+            }
+            ColumnType::Blob => Field::Blob(reader.read_length_prefixed()?.to_vec()),
+        };
+        record.push(field);
+    }
+    Ok(record)
+}
+
+/// Most bytes a value of `column_type` takes up in a record, or `None` for types
+/// without a bound like `text`.
+pub(super) fn max_field_size(column_type: &ColumnType) -> Option<usize> {
+    let size = match column_type {
+        ColumnType::Int | ColumnType::Real | ColumnType::Time | ColumnType::Timestamp => 8,
+        ColumnType::Bool => 1,
+        ColumnType::Date => 4,
+        ColumnType::Decimal(..) => 16,
+        // a character takes up to 4 bytes in utf-8
+        ColumnType::Varchar(length) | ColumnType::Char(length) => {
+            let bytes = (*length as usize).saturating_mul(4);
+            varint_size(bytes as u64) + bytes
+        }
+        ColumnType::Text | ColumnType::Blob => return None,
+    };
+    Some(size)
+}
+
+fn write_length_prefixed(bytes: &mut Vec<u8>, value: &[u8]) {
+    write_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value);
+}
+
+pub(super) fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn varint_size(value: u64) -> usize {
+    (u64::BITS - value.leading_zeros()).max(1).div_ceil(7) as usize
+}
+
+/// Reads fields off the front of a record, failing instead of reading past its end.
+pub(super) struct Reader<'a> {
+    pub bytes: &'a [u8],
+    pub offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn take(&mut self, len: usize) -> BEResult<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|it| *it <= self.bytes.len());
+        let Some(end) = end else {
+            return Err(BEErrors::InternalError(format!(
+                "value of {len} bytes at offset {} overruns the page",
+                self.offset
+            )));
+        };
+        let value = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(value)
+    }
+
+    pub(super) fn read_array<const N: usize>(&mut self) -> BEResult<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    pub(super) fn read_varint(&mut self) -> BEResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..u64::BITS).step_by(7) {
+            let [byte] = self.read_array()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BEErrors::InternalError(format!(
+            "varint at offset {} is too long",
+            self.offset
+        )))
+    }
+
+    fn read_length_prefixed(&mut self) -> BEResult<&'a [u8]> {
+        let len = self.read_varint()?;
+        let len = usize::try_from(len).map_err(|_| {
+            BEErrors::InternalError(format!(
+                "length {len} at offset {} is too large",
+                self.offset
+            ))
+        })?;
+        self.take(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(0, vec![0x00])]
+    #[case(127, vec![0x7f])]
+    #[case(128, vec![0x80, 0x01])]
+    #[case(300, vec![0xac, 0x02])]
+    #[case(u64::MAX, vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01])]
+    fn test_varint(#[case] value: u64, #[case] encoded: Vec<u8>) -> BEResult<()> {
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, value);
+        assert_eq!(encoded, bytes);
+        assert_eq!(encoded.len(), varint_size(value));
+
+        let mut reader = Reader {
+            bytes: &bytes,
+            offset: 0,
+        };
+        assert_eq!(value, reader.read_varint()?);
+        assert_eq!(bytes.len(), reader.offset);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encoding_is_fixed() -> BEResult<()> {
+        let date = NaiveDate::from_ymd_opt(1, 1, 2).unwrap();
+        let fields = vec![
+            Field::Int(-2),
+            Field::Bool(true),
+            Field::Date(date),
+            Field::Text(String::from("hé")),
+            Field::Decimal(Decimal {
+                value: 258,
+                scale: 2,
+            }),
+        ];
+        let bytes = encode(&fields)?;
+        let mut expected = vec![0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        expected.extend([0x02, 0x00, 0x00, 0x00]);
+        expected.extend([0x03, b'h', 0xc3, 0xa9]);
+        expected.extend([0x02, 0x01]);
+        expected.extend([0; 14]);
+        assert_eq!(expected, bytes);
+
+        let column_types = [
+            ColumnType::Int,
+            ColumnType::Bool,
+            ColumnType::Date,
+            ColumnType::Varchar(2),
+            ColumnType::Decimal(5, 2),
+        ];
+        assert_eq!(fields, decode(&bytes, column_types.iter())?);
+        assert!(matches!(
+            decode(&bytes[..bytes.len() - 1], column_types.iter()),
+            Err(BEErrors::InternalError(_))
+        ));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case(ColumnType::Date, i32::MAX.to_le_bytes().to_vec())]
+    #[case(ColumnType::Time, (-1i64).to_le_bytes().to_vec())]
+    #[case(ColumnType::Time, (86_400_000_000i64).to_le_bytes().to_vec())]
+    #[case(ColumnType::Timestamp, i64::MAX.to_le_bytes().to_vec())]
+    fn test_out_of_range_temporal_values(#[case] column_type: ColumnType, #[case] bytes: Vec<u8>) {
+        assert!(matches!(
+            decode(&bytes, [column_type].iter()),
+            Err(BEErrors::InternalError(_))
+        ));
+    }
+}
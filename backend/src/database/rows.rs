@@ -0,0 +1,285 @@
+use std::{collections::HashMap, sync::Arc};
+
+use frontend::{
+    command::statement::{expression::Expression, insert::Value},
+    definitions::table_definition::TableName,
+    ColumnName, TableDefinition,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    errors::{BEErrors, BEResult},
+    mapping::from_row,
+    vm::expression::evaluate_predicate,
+};
+
+use super::{Field, Row, Sharable, Table};
+
+/// Lazy cursor over the rows of a table.
+/// Every call to `next` locks the table only long enough to decode a single row,
+/// so memory use stays constant regardless of table size and consumers can stop early.
+/// Rows not matching the optional filter are skipped.
+/// The cursor ends after the first error, including when its table is dropped, altered,
+/// truncated or vacuumed while rows are still being read.
+#[derive(Debug, Clone)]
+pub struct Rows {
+    table_definitions: Sharable<HashMap<TableName, Arc<TableDefinition>>>,
+    tables: Sharable<HashMap<TableName, Table>>,
+    table_name: TableName,
+    columns: Vec<ColumnName>,
+    column_indexes: Vec<usize>,
+    filter: Option<Expression>,
+    table_definition: Arc<TableDefinition>,
+    generation: u64,
+    page_index: usize,
+    slot_index: usize,
+    done: bool,
+}
+
+impl Rows {
+    pub(super) fn new(
+        table_definitions: Sharable<HashMap<TableName, Arc<TableDefinition>>>,
+        tables: Sharable<HashMap<TableName, Table>>,
+        table_name: TableName,
+        columns: Vec<ColumnName>,
+        filter: Option<Expression>,
+        table_definition: Arc<TableDefinition>,
+    ) -> BEResult<Rows> {
+        let column_index = |ColumnName(name): &ColumnName| {
+            table_definition
+                .columns
+                .iter()
+                .position(|it| it.0 == *name)
+                .ok_or_else(|| BEErrors::MissingColumn(format!("Column {name} not found")))
+        };
+        let column_indexes = columns.iter().map(column_index).collect::<BEResult<_>>()?;
+        if let Some(filter) = filter.as_ref() {
+            for column in filter.columns() {
+                column_index(column)?;
+            }
+        }
+
+        let generation = tables
+            .lock()
+            .unwrap()
+            .get(&table_name)
+            .map_or(0, |table| table.generation);
+        Ok(Rows {
+            table_definitions,
+            tables,
+            table_name,
+            columns,
+            column_indexes,
+            filter,
+            table_definition,
+            generation,
+            page_index: 0,
+            slot_index: 0,
+            done: false,
+        })
+    }
+
+    fn project(&self, record: Vec<Field>) -> Row {
+        self.columns
+            .iter()
+            .zip(self.column_indexes.iter())
+            .map(|(name, index)| match &record[*index] {
+                Field::Blob(bytes) => Value::NamedBlob(name.0.clone(), bytes.clone()),
+                field => Value::NamedValue(name.0.clone(), field.to_string()),
+            })
+            .collect()
+    }
+
+    /// Columns every produced row is projected to, in output order.
+    pub fn columns(&self) -> &[ColumnName] {
+        &self.columns
+    }
+
+    /// Maps every remaining row into `T`, see [`crate::mapping::from_row`].
+    pub fn deserialize<T: DeserializeOwned>(self) -> impl Iterator<Item = BEResult<T>> {
+        let table_definition = Arc::clone(&self.table_definition);
+        self.map(move |row| from_row(row?, &table_definition))
+    }
+
+    fn read_next(&mut self) -> Option<BEResult<Row>> {
+        // locked in the same order as schema changes lock them
+        let definitions = self.table_definitions.lock().unwrap();
+        let tables = self.tables.lock().unwrap();
+        let (Some(definition), Some(table)) = (
+            definitions.get(&self.table_name),
+            tables.get(&self.table_name),
+        ) else {
+            return Some(Err(BEErrors::MissingTable(self.table_name.0.clone())));
+        };
+        // a table dropped and created again starts over at the first generation
+        if !Arc::ptr_eq(definition, &self.table_definition) || table.generation != self.generation {
+            return Some(Err(BEErrors::TableChanged(self.table_name.0.clone())));
+        }
+
+        loop {
+            let page = table.pages.get(self.page_index)?;
+            if self.slot_index < page.row_count() {
+                let record =
+                    table.read_record(self.page_index, self.slot_index, &self.table_definition);
+                self.slot_index += 1;
+                let record = match record {
+                    Ok(record) => record,
+                    Err(err) => return Some(Err(err)),
+                };
+                if let Some(filter) = self.filter.as_ref() {
+                    match evaluate_predicate(filter, &record, &self.table_definition) {
+                        Ok(true) => {}
+                        Ok(false) => continue,
+                        Err(err) => return Some(Err(err)),
+                    }
+                }
+                return Some(Ok(self.project(record)));
+            }
+
+            self.page_index += 1;
+            self.slot_index = 0;
+        }
+    }
+}
+
+impl Iterator for Rows {
+    type Item = BEResult<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.read_next();
+        self.done = !matches!(item, Some(Ok(_)));
+        item
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        database::Database,
+        fixtures::{
+            query, run, select, student_database, temp_database, temp_db_path, TempDatabase,
+            TempDbPath,
+        },
+    };
+
+    #[rstest]
+    fn test_rows_stream_across_pages(
+        #[from(student_database)] database: TempDatabase,
+    ) -> BEResult<()> {
+        for id in 0..400 {
+            run(
+                &database,
+                &format!("insert into student (id, name) values ({id}, student{id});"),
+            )?;
+        }
+        assert!(
+            database.tables.lock().unwrap()[&TableName(String::from("student"))]
+                .pages
+                .len()
+                > 1
+        );
+
+        let rows = query(&database, "select name from student;")?;
+        assert_eq!(&[ColumnName::from("name")], rows.columns());
+        let names = rows
+            .skip(200)
+            .take(2)
+            .map(|row| row.map(|mut it| it.remove(0).value()))
+            .collect::<BEResult<Vec<_>>>()?;
+        assert_eq!(vec!["student200", "student201"], names);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_typed_insert_and_select(temp_db_path: TempDbPath) -> BEResult<()> {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Student {
+            name: String,
+            id: i64,
+        }
+
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table student(id int, name text);")?;
+        let student = Student {
+            name: String::from("ron weasley"),
+            id: 7,
+        };
+        database.insert("student", &student)?;
+
+        let students = select::<Student>(&database, "select id, name from student;")?;
+        assert_eq!(vec![student], students);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_cursor_ends_when_table_changes(
+        #[from(temp_database)] database: TempDatabase,
+    ) -> BEResult<()> {
+        run(&database, "create table student(id int);")?;
+        run(&database, "insert into student values (1), (2), (3);")?;
+
+        let select = || query(&database, "select id from student;");
+        let mut rows = select()?;
+        assert!(matches!(rows.next(), Some(Ok(_))));
+        run(&database, "drop table student;")?;
+        assert!(matches!(rows.next(), Some(Err(BEErrors::MissingTable(_)))));
+        assert!(rows.next().is_none());
+
+        run(&database, "create table student(id int);")?;
+        run(&database, "insert into student values (1), (2);")?;
+        let rows = select()?;
+        let mut skipped = select()?;
+        skipped.next();
+        run(&database, "alter table student add grade int default 1;")?;
+        assert_eq!(1, rows.count());
+        let remaining = skipped.collect::<Vec<_>>();
+        assert_eq!(1, remaining.len());
+        assert!(matches!(remaining[0], Err(BEErrors::TableChanged(_))));
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_cursor_fails_after_truncate(
+        #[from(student_database)] database: TempDatabase,
+    ) -> BEResult<()> {
+        run(
+            &database,
+            "insert into student values (1, john), (2, jane), (3, bob);",
+        )?;
+        let mut rows = query(&database, "select id from student;")?;
+        assert!(matches!(rows.next(), Some(Ok(_))));
+
+        run(&database, "truncate table student;")?;
+        run(
+            &database,
+            "insert into student values (4, alice), (5, carol), (6, dave);",
+        )?;
+        assert!(matches!(rows.next(), Some(Err(BEErrors::TableChanged(_)))));
+        assert!(rows.next().is_none());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_cursor_fails_after_vacuum(
+        #[from(student_database)] database: TempDatabase,
+    ) -> BEResult<()> {
+        run(
+            &database,
+            "insert into student values (1, john), (2, jane), (3, bob);",
+        )?;
+        run(&database, "alter table student drop column name;")?;
+        let mut rows = query(&database, "select id from student;")?;
+        assert!(matches!(rows.next(), Some(Ok(_))));
+
+        run(&database, "vacuum student;")?;
+        assert!(matches!(rows.next(), Some(Err(BEErrors::TableChanged(_)))));
+        assert!(rows.next().is_none());
+        Ok(())
+    }
+}
@@ -1,55 +1,312 @@
-use frontend::{command::statement::insert::Value, ColumnName, TableDefinition};
+use frontend::{
+    command::statement::insert::Value, definitions::table_definition::TableName, TableDefinition,
+};
 
-use crate::{database::PAGE_SIZE, errors::BEResult};
+use crate::errors::{BEErrors, BEResult};
 
-use super::page::Page;
+use super::{
+    page::{Page, StoredRecord},
+    record, Field,
+};
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub(super) struct Table {
     pub pages: Vec<Page>,
+    pub page_size: usize,
+    /// Bumped whenever rows are removed or rewritten in place, so cursors over the
+    /// table can tell their position no longer points at the row they expect.
+    pub generation: u64,
 }
 
 impl Table {
-    pub(super) fn new(data: Vec<u8>) -> Table {
+    pub(super) fn new(page_size: usize) -> Table {
+        Table {
+            pages: Vec::new(),
+            page_size,
+            generation: 0,
+        }
+    }
+
+    /// Splits the data file of table `name` into pages, checking every page is whole and
+    /// has the checksum it was written with.
+    pub(super) fn load(name: &TableName, data: Vec<u8>, page_size: usize) -> BEResult<Table> {
         let pages = data
-            .chunks(PAGE_SIZE)
-            .map(|it| Page::new(it.to_vec()))
-            .collect();
-        Table { pages }
+            .chunks(page_size)
+            .enumerate()
+            .map(|(index, it)| {
+                if !Page::is_intact(it, page_size) {
+                    return Err(BEErrors::CorruptedPage(name.0.clone(), index));
+                }
+                Ok(Page::new(it.to_vec(), page_size))
+            })
+            .collect::<BEResult<_>>()?;
+        Ok(Table {
+            pages,
+            page_size,
+            generation: 0,
+        })
     }
 }
 
 impl Table {
-    pub(super) fn read_pages(
-        &self,
-        column_names: Vec<ColumnName>,
+    /// Writes all `rows`, or none of them if any row fails validation.
+    /// Tables whose largest row fits inline, judged by [`Page::max_record_size`], are
+    /// known up front to need no overflow pages.
+    pub(super) fn write_rows(
+        &mut self,
+        rows: Vec<Vec<Value>>,
         table_definition: &TableDefinition,
-    ) -> BEResult<Vec<Vec<Value>>> {
-        let mut result = Vec::new();
-        for page in self.pages.iter() {
-            if let Some(mut values) = page.read_all_rows(&column_names, table_definition)? {
-                result.append(&mut values);
+    ) -> BEResult<()> {
+        let rows = rows
+            .into_iter()
+            .map(|values| Page::encode(values, table_definition))
+            .collect::<BEResult<Vec<_>>>()?;
+
+        let may_overflow = Page::max_record_size(table_definition)
+            .is_none_or(|size| size > Page::overflow_threshold(self.page_size));
+        for fields in rows {
+            if may_overflow {
+                self.write_fields(fields, table_definition.version())?;
+            } else {
+                self.write_inline(&record::encode(&fields)?, table_definition.version())?;
             }
         }
-        Ok(result)
+        Ok(())
     }
 
-    pub(super) fn write_row(
-        &mut self,
-        values: Vec<Value>,
+    /// Appends a row to the last slotted page, starting a new page when it is full.
+    /// Rows larger than [`Page::overflow_threshold`] are written to overflow pages first.
+    pub(super) fn write_fields(&mut self, fields: Vec<Field>, version: usize) -> BEResult<()> {
+        let bytes = record::encode(&fields)?;
+        if bytes.len() <= Page::overflow_threshold(self.page_size) {
+            return self.write_inline(&bytes, version);
+        }
+
+        let first_page = self.pages.len();
+        let capacity = Page::overflow_capacity(self.page_size);
+        let chunk_count = bytes.len().div_ceil(capacity);
+        for (index, chunk) in bytes.chunks(capacity).enumerate() {
+            let next = Some(first_page + index + 1).filter(|_| index + 1 < chunk_count);
+            self.pages
+                .push(Page::overflow(chunk, next, self.page_size)?);
+        }
+        // the pointer is a page index and the length, small enough for any page
+        self.slotted_page_for(4 + 10)
+            .write_overflow_pointer(first_page, bytes.len(), version)
+    }
+
+    fn write_inline(&mut self, bytes: &[u8], version: usize) -> BEResult<()> {
+        self.slotted_page_for(bytes.len())
+            .write_record(bytes, version)
+    }
+
+    fn slotted_page_for(&mut self, len: usize) -> &mut Page {
+        let last_slotted = self.pages.iter().rposition(Page::is_slotted);
+        match last_slotted {
+            Some(index) if self.pages[index].fits(len) => &mut self.pages[index],
+            _ => {
+                self.pages.push(Page::slotted(self.page_size));
+                self.pages.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Rewrites every row into fresh pages, densely packed and under the current schema
+    /// version of `table_definition`.
+    pub(super) fn compacted(&self, table_definition: &TableDefinition) -> BEResult<Table> {
+        let mut compacted = Table::new(self.page_size);
+        compacted.generation = self.generation + 1;
+        for (index, page) in self.pages.iter().enumerate() {
+            for slot in 0..page.row_count() {
+                let fields = self.read_record(index, slot, table_definition)?;
+                compacted.write_fields(fields, table_definition.version())?;
+            }
+        }
+        Ok(compacted)
+    }
+
+    /// Reads the record in `slot` of page `page_index`, following its overflow pages.
+    pub(super) fn read_record(
+        &self,
+        page_index: usize,
+        slot: usize,
         table_definition: &TableDefinition,
-    ) -> BEResult<()> {
-        if let Some(last_page) = self.pages.last_mut() {
-            if let Some(_) = last_page.available_slot_pos() {
-                last_page.write(values, &table_definition)?;
-                return Ok(());
+    ) -> BEResult<Vec<Field>> {
+        let Some(page) = self.pages.get(page_index) else {
+            return Err(BEErrors::InternalError(format!(
+                "page {page_index} is not part of the table"
+            )));
+        };
+        match page.stored_record(slot)? {
+            (StoredRecord::Inline(bytes), version) => {
+                Page::decode_record(bytes, version, table_definition)
             }
+            (StoredRecord::Overflow { first_page, len }, version) => {
+                let bytes = self.read_overflow(first_page, len)?;
+                Page::decode_record(&bytes, version, table_definition)
+            }
+        }
+    }
+
+    fn read_overflow(&self, first_page: usize, len: usize) -> BEResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut next = Some(first_page);
+        // a chain can not be longer than the table, which also stops on cycles
+        for _ in 0..self.pages.len() {
+            let Some(index) = next else {
+                break;
+            };
+            let Some(page) = self.pages.get(index).filter(|it| it.is_overflow()) else {
+                return Err(BEErrors::InternalError(format!(
+                    "page {index} is not an overflow page"
+                )));
+            };
+            bytes.extend_from_slice(page.overflow_chunk()?);
+            next = page.overflow_next();
+        }
+        if next.is_some() || bytes.len() != len {
+            return Err(BEErrors::InternalError(format!(
+                "overflow pages from page {first_page} do not hold a record of {len} bytes"
+            )));
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        database::{Database, DEFAULT_PAGE_SIZE},
+        fixtures::{query, run, select, student_database, temp_db_path, TempDatabase, TempDbPath},
+    };
+
+    #[rstest]
+    fn test_multi_row_insert_is_all_or_nothing(
+        #[from(student_database)] database: TempDatabase,
+    ) -> BEResult<()> {
+        assert!(matches!(
+            run(
+                &database,
+                "insert into student (id, name) values (1, john), (two, jane);"
+            ),
+            Err(BEErrors::MismatchedDataType(..))
+        ));
+        assert!(matches!(
+            run(&database, "insert into student (id, id) values (1, 2);"),
+            Err(BEErrors::DuplicateColumn(column)) if column == "id"
+        ));
+        run(
+            &database,
+            "insert into student (name, id) values (john, 1), (jane, 2);",
+        )?;
+
+        let students = select::<(i64, String)>(&database, "select id, name from student;")?;
+        assert_eq!(
+            vec![(1, String::from("john")), (2, String::from("jane"))],
+            students
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_max_record_size(temp_db_path: TempDbPath) -> BEResult<()> {
+        let bounded = TableDefinition::from_str(
+            "create table tag(id int, label varchar(10), code char(2), active bool);",
+        )?;
+        // 8 for the int, a length byte and 4 bytes per character for each text and 1 for the bool
+        assert_eq!(Some(8 + 41 + 9 + 1), Page::max_record_size(&bounded));
+        let unbounded = TableDefinition::from_str("create table note(id int, body text);")?;
+        assert_eq!(None, Page::max_record_size(&unbounded));
+
+        let database = Database::open(&temp_db_path)?;
+        run(
+            &database,
+            "create table essay(title varchar(10), body varchar(2000));",
+        )?;
+        run(
+            &database,
+            &format!(
+                "insert into essay values (short, a), (long, {});",
+                "b".repeat(2000)
+            ),
+        )?;
+        let tables = database.tables.lock().unwrap();
+        let pages = &tables[&TableName(String::from("essay"))].pages;
+        assert_eq!(1, pages.iter().filter(|it| it.is_overflow()).count());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_large_values(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(
+            &database,
+            "create table file(name text, body text, data blob);",
+        )?;
+        let body = "zoë ".repeat(5_000);
+        let data = (0..10_000).map(|it| it as u8).collect::<Vec<_>>();
+        let insert = database.prepare("insert into file values (?, ?, ?);")?;
+        insert.execute(&["before".into(), "".into(), Vec::new().into()])?;
+        insert.execute(&["large".into(), body.as_str().into(), data.clone().into()])?;
+        insert.execute(&["after".into(), "".into(), vec![1].into()])?;
+        database.flush()?;
+
+        drop((insert, database));
+        let reopened = Database::open(&temp_db_path)?;
+        let rows = query(&reopened, "select name from file;")?;
+        assert_eq!(
+            vec!["before", "large", "after"],
+            rows.deserialize::<(String,)>()
+                .map(|row| row.map(|it| it.0))
+                .collect::<BEResult<Vec<_>>>()?
+        );
+        let rows = query(
+            &reopened,
+            "select body, data from file where name = 'large';",
+        )?;
+        assert_eq!(
+            vec![(body, data)],
+            rows.deserialize::<(String, Vec<u8>)>()
+                .collect::<BEResult<Vec<_>>>()?
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_corrupted_pages(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table student(id int, name text);")?;
+        for id in 0..400 {
+            run(
+                &database,
+                &format!("insert into student values ({id}, student{id});"),
+            )?;
         }
+        database.flush()?;
+        drop(database);
+        let data_path = temp_db_path.join("data/student");
+        let data = std::fs::read(&data_path)?;
+        assert!(Database::open(&temp_db_path).is_ok());
 
-        let mut page = Page::default();
-        page.write(values, &table_definition)?;
-        self.pages.push(page);
+        let mut flipped = data.clone();
+        flipped[DEFAULT_PAGE_SIZE + 100] ^= 1;
+        std::fs::write(&data_path, &flipped)?;
+        assert!(matches!(
+            Database::open(&temp_db_path),
+            Err(BEErrors::CorruptedPage(table, 1)) if table == "student"
+        ));
 
+        std::fs::write(&data_path, &data[..data.len() - 10])?;
+        let last_page = data.len() / DEFAULT_PAGE_SIZE - 1;
+        assert!(matches!(
+            Database::open(&temp_db_path),
+            Err(BEErrors::CorruptedPage(_, page)) if page == last_page
+        ));
         Ok(())
     }
 }
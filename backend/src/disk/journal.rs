@@ -0,0 +1,310 @@
+//! Redo journal making a flush all-or-nothing.
+//!
+//! A flush first writes every change it is about to make to `flush.journal.tmp`, syncs it
+//! and renames it to `flush.journal`, which is the point the flush takes effect. Only then
+//! are the changes applied to the files of the database, after which the journal is
+//! removed. Pages are thereby written twice, once to the journal and once in place, so a
+//! page torn by a crash while applying is written again from the journal.
+//!
+//! A database opened with a journal present had a flush interrupted while applying it
+//! and applies it again, which writes the same bytes to the same places. A leftover
+//! `flush.journal.tmp` is a flush interrupted before it took effect and is discarded.
+//!
+//! Entries are a tag byte followed by the table name and the payload, with lengths as
+//! little-endian `u32` and page indexes and file lengths as little-endian `u64`.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use frontend::{definitions::table_definition::TableName, TableDefinition};
+
+use crate::{
+    database::stamp_checksum,
+    errors::{BEErrors, BEResult},
+};
+
+use super::{sync_dir, write_atomically, DatabaseMetadata, METADATA_FILE, TEMP_SUFFIX};
+
+const JOURNAL_FILE: &str = "flush.journal";
+
+const DELETE_TABLE: u8 = 1;
+const WRITE_DEFINITION: u8 = 2;
+const WRITE_PAGE: u8 = 3;
+const TRUNCATE_DATA_FILE: u8 = 4;
+const WRITE_METADATA: u8 = 5;
+
+/// Changes of a flush being collected, which take effect once committed by
+/// [`super::DiskAccessor::commit_flush`].
+pub(crate) struct Journal {
+    writer: BufWriter<File>,
+    temp_path: PathBuf,
+    path: PathBuf,
+}
+
+impl Journal {
+    pub(super) fn create(base_path: &Path) -> BEResult<Journal> {
+        let path = base_path.join(JOURNAL_FILE);
+        let temp_path = base_path.join(format!("{JOURNAL_FILE}{TEMP_SUFFIX}"));
+        Ok(Journal {
+            writer: BufWriter::new(File::create(&temp_path)?),
+            temp_path,
+            path,
+        })
+    }
+
+    fn write_entry(&mut self, tag: u8, name: &str) -> BEResult<()> {
+        self.writer.write_all(&[tag])?;
+        self.write_bytes(name.as_bytes())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> BEResult<()> {
+        let len = u32::try_from(bytes.len()).map_err(|_| {
+            BEErrors::InternalError(format!("{} bytes do not fit a journal entry", bytes.len()))
+        })?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(bytes)?;
+        Ok(())
+    }
+
+    /// Removes the definition and data files of the table.
+    pub fn delete_table(&mut self, name: &TableName) -> BEResult<()> {
+        self.write_entry(DELETE_TABLE, &name.0)
+    }
+
+    pub fn write_table_definition(
+        &mut self,
+        name: &TableName,
+        definition: &TableDefinition,
+    ) -> BEResult<()> {
+        self.write_entry(WRITE_DEFINITION, &name.0)?;
+        self.write_bytes(serde_json::to_string(definition)?.as_bytes())
+    }
+
+    /// Writes `data` as page `page_num` of the table, taking its length as the page size,
+    /// with its checksum filled in.
+    pub fn write_data_page(
+        &mut self,
+        name: &TableName,
+        page_num: usize,
+        data: &[u8],
+    ) -> BEResult<()> {
+        let mut page = data.to_vec();
+        stamp_checksum(&mut page);
+        self.write_entry(WRITE_PAGE, &name.0)?;
+        self.writer
+            .write_all(&((page_num * page.len()) as u64).to_le_bytes())?;
+        self.write_bytes(&page)
+    }
+
+    /// Shrinks the data file of the table to `len` bytes, dropping pages past the end.
+    pub fn truncate_data_file(&mut self, name: &TableName, len: usize) -> BEResult<()> {
+        self.write_entry(TRUNCATE_DATA_FILE, &name.0)?;
+        self.writer.write_all(&(len as u64).to_le_bytes())?;
+        Ok(())
+    }
+
+    pub fn write_metadata(&mut self, metadata: &DatabaseMetadata) -> BEResult<()> {
+        self.write_entry(WRITE_METADATA, "")?;
+        self.write_bytes(serde_json::to_string(metadata)?.as_bytes())
+    }
+
+    /// Makes the journal durable and renames it into place, after which its changes are
+    /// applied even if the process dies before getting to it.
+    pub(super) fn commit(self) -> BEResult<()> {
+        let file = self.writer.into_inner().map_err(|err| err.into_error())?;
+        file.sync_all()?;
+        fs::rename(&self.temp_path, &self.path)?;
+        sync_dir(self.path.parent().unwrap_or(Path::new(".")))
+    }
+}
+
+/// Whether a committed journal was left in `base_path` by an interrupted flush.
+pub(super) fn is_committed(base_path: &Path) -> bool {
+    base_path.join(JOURNAL_FILE).exists()
+}
+
+/// Applies a committed journal left in `base_path` and removes it, discarding a journal
+/// which was never committed.
+pub(super) fn recover(base_path: &Path) -> BEResult<()> {
+    match fs::remove_file(base_path.join(format!("{JOURNAL_FILE}{TEMP_SUFFIX}"))) {
+        Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+        _ => {}
+    }
+    let path = base_path.join(JOURNAL_FILE);
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    apply(base_path, BufReader::new(file))?;
+    fs::remove_file(&path)?;
+    sync_dir(base_path)
+}
+
+fn apply(base_path: &Path, mut reader: impl Read) -> BEResult<()> {
+    let meta_dir = base_path.join("meta");
+    let data_dir = base_path.join("data");
+    // data files written to, synced once all entries are applied
+    let mut data_files: HashMap<String, File> = HashMap::new();
+    let mut open_data_file = |name: &str| -> BEResult<File> {
+        if let Some(file) = data_files.get(name) {
+            return Ok(file.try_clone()?);
+        }
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(data_dir.join(name))?;
+        data_files.insert(name.to_string(), file.try_clone()?);
+        Ok(file)
+    };
+
+    loop {
+        let mut tag = [0];
+        match reader.read_exact(&mut tag) {
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+            result => result?,
+        }
+        let name = String::from_utf8(read_bytes(&mut reader)?).map_err(|err| {
+            BEErrors::InternalError(format!("table name in journal is not utf-8, {err}"))
+        })?;
+
+        match tag[0] {
+            DELETE_TABLE => {
+                for dir in [&meta_dir, &data_dir] {
+                    match fs::remove_file(dir.join(&name)) {
+                        Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+                        _ => {}
+                    }
+                }
+            }
+            WRITE_DEFINITION => write_atomically(&meta_dir.join(&name), &read_bytes(&mut reader)?)?,
+            WRITE_PAGE => {
+                let offset = read_u64(&mut reader)?;
+                let page = read_bytes(&mut reader)?;
+                let mut file = open_data_file(&name)?;
+                file.seek(SeekFrom::Start(offset))?;
+                file.write_all(&page)?;
+            }
+            TRUNCATE_DATA_FILE => {
+                let len = read_u64(&mut reader)?;
+                let file = open_data_file(&name)?;
+                if file.metadata()?.len() > len {
+                    file.set_len(len)?;
+                }
+            }
+            WRITE_METADATA => {
+                write_atomically(&base_path.join(METADATA_FILE), &read_bytes(&mut reader)?)?
+            }
+            tag => {
+                return Err(BEErrors::InternalError(format!(
+                    "unknown journal entry {tag}"
+                )))
+            }
+        }
+    }
+
+    for file in data_files.values() {
+        file.sync_all()?;
+    }
+    sync_dir(&data_dir)?;
+    sync_dir(&meta_dir)
+}
+
+fn read_u64(reader: &mut impl Read) -> BEResult<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_bytes(reader: &mut impl Read) -> BEResult<Vec<u8>> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::{
+        database::Database,
+        disk::DiskAccessor,
+        fixtures::{run, select, temp_db_path, TempDbPath},
+    };
+
+    fn note_ids(database: &Database) -> BEResult<Vec<(i64,)>> {
+        select(database, "select id from note;")
+    }
+
+    #[rstest]
+    fn test_committed_journal_is_applied_on_open(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table note(id int);")?;
+        run(&database, "insert into note values (1), (2);")?;
+        database.flush()?;
+        drop(database);
+        assert_eq!(vec!["data", "database.json", "database.lock", "meta"], {
+            let mut names = fs::read_dir(&temp_db_path)?
+                .map(|it| Ok(it?.file_name().to_string_lossy().to_string()))
+                .collect::<BEResult<Vec<_>>>()?;
+            names.sort();
+            names
+        });
+
+        let definition: TableDefinition =
+            serde_json::from_slice(&fs::read(temp_db_path.join("meta/note"))?)?;
+        let pages = fs::read(temp_db_path.join("data/note"))?;
+        // a crash while applying the journal left the page torn
+        fs::write(temp_db_path.join("data/note"), &pages[..pages.len() / 2])?;
+        assert!(Database::open(&temp_db_path).is_err());
+
+        let disk_accessor = DiskAccessor::new(temp_db_path.to_path_buf(), false)?;
+        let name = definition.name.clone();
+        let mut journal = disk_accessor.begin_flush()?;
+        journal.write_table_definition(&name, &definition)?;
+        journal.write_data_page(&name, 0, &pages)?;
+        journal.truncate_data_file(&name, pages.len())?;
+        journal.commit()?;
+        drop(disk_accessor);
+
+        let reopened = Database::open(&temp_db_path)?;
+        assert_eq!(vec![(1,), (2,)], note_ids(&reopened)?);
+        assert!(!temp_db_path.join(JOURNAL_FILE).exists());
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_uncommitted_journal_is_discarded(temp_db_path: TempDbPath) -> BEResult<()> {
+        let database = Database::open(&temp_db_path)?;
+        run(&database, "create table note(id int);")?;
+        run(&database, "insert into note values (1);")?;
+        database.flush()?;
+        drop(database);
+
+        let disk_accessor = DiskAccessor::new(temp_db_path.to_path_buf(), false)?;
+        let mut journal = disk_accessor.begin_flush()?;
+        journal.delete_table(&TableName(String::from("note")))?;
+        // dropped without being committed, as if the process died while writing it
+        drop(journal);
+        assert!(temp_db_path
+            .join(format!("{JOURNAL_FILE}{TEMP_SUFFIX}"))
+            .exists());
+        drop(disk_accessor);
+
+        let reopened = Database::open(&temp_db_path)?;
+        assert_eq!(vec![(1,)], note_ids(&reopened)?);
+        assert!(!temp_db_path
+            .join(format!("{JOURNAL_FILE}{TEMP_SUFFIX}"))
+            .exists());
+        Ok(())
+    }
+}
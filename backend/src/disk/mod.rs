@@ -1,41 +1,93 @@
-use frontend::{definitions::table_definition::TableName, TableDefinition};
+use serde::{Deserialize, Serialize};
 use std::{
-    fs::{self, File, OpenOptions},
-    io::{Read, Seek, SeekFrom, Write},
-    path::PathBuf,
+    fs::{self, File, TryLockError},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
 };
 
 pub(crate) mod data_iterator;
 pub(crate) mod def_iterator;
-use crate::{database::PAGE_SIZE, errors::BEResult};
+mod journal;
+pub(crate) use self::journal::Journal;
+use crate::{
+    database::DEFAULT_PAGE_SIZE,
+    errors::{BEErrors, BEResult},
+};
+
+const METADATA_FILE: &str = "database.json";
+const LOCK_FILE: &str = "database.lock";
+// suffix of files being written before they replace the file of the same name
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// Facts about the whole database, kept in `database.json` next to the `meta` and
+/// `data` directories. Databases written before it existed have no such file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DatabaseMetadata {
+    /// Encoding of the pages in `data`, see [`crate::database::RECORD_FORMAT_VERSION`].
+    pub format_version: u32,
+    /// Size of the pages in `data`, which was always the default before it was recorded.
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+}
+
+fn default_page_size() -> usize {
+    DEFAULT_PAGE_SIZE
+}
 
 #[derive(Debug)]
 pub(crate) struct DiskAccessor {
     base_path: String,
+    // advisory lock keeping other processes from opening the database while it is open
+    _lock: Option<File>,
 }
 
 impl DiskAccessor {
-    pub fn new(dir_path: PathBuf) -> DiskAccessor {
+    /// Opens the database directory, creating its layout if it does not exist, and takes
+    /// the lock on it, which is held until the accessor is dropped. Read-only accessors
+    /// never write to the directory, they share the lock with each other but not with
+    /// accessors which write, and take none for databases which were never locked.
+    pub fn new(dir_path: PathBuf, read_only: bool) -> BEResult<DiskAccessor> {
+        if read_only && !dir_path.is_dir() {
+            return Err(std::io::Error::new(
+                ErrorKind::NotFound,
+                format!("no database at {}", dir_path.to_string_lossy()),
+            )
+            .into());
+        }
         if !dir_path.exists() {
-            fs::create_dir_all(&dir_path)
-                .expect(&format!("failed to create database dir {:?}", dir_path));
+            fs::create_dir_all(&dir_path)?;
 
             // Create the subdirectories 'meta' and 'data'
-            let meta_dir = dir_path.join("meta");
-            let data_dir = dir_path.join("data");
-            fs::create_dir(&meta_dir).expect(&format!(
-                "failed to create database meta dir at {:?}",
-                dir_path
-            ));
-            fs::create_dir(&data_dir).expect(&format!(
-                "failed to create database data dir at {:?}",
-                dir_path
-            ));
+            fs::create_dir(dir_path.join("meta"))?;
+            fs::create_dir(dir_path.join("data"))?;
         }
 
-        DiskAccessor {
+        let lock_path = dir_path.join(LOCK_FILE);
+        let lock = if read_only {
+            match File::open(&lock_path) {
+                Ok(lock) => Some((lock.try_lock_shared(), lock)),
+                Err(err) if err.kind() == ErrorKind::NotFound => None,
+                Err(err) => return Err(err.into()),
+            }
+        } else {
+            let lock = File::create(&lock_path)?;
+            Some((lock.try_lock(), lock))
+        };
+        let lock = match lock {
+            Some((Ok(()), lock)) => Some(lock),
+            Some((Err(TryLockError::WouldBlock), _)) => {
+                return Err(BEErrors::DatabaseLocked(
+                    dir_path.to_string_lossy().to_string(),
+                ))
+            }
+            Some((Err(TryLockError::Error(err)), _)) => return Err(err.into()),
+            None => None,
+        };
+
+        Ok(DiskAccessor {
             base_path: dir_path.to_string_lossy().to_string(),
-        }
+            _lock: lock,
+        })
     }
 
     fn read_file_as_bytes(&self, file_name: impl Into<String>) -> BEResult<Vec<u8>> {
@@ -52,9 +104,10 @@ impl DiskAccessor {
         let mut path_buf = PathBuf::from(self.base_path.clone());
         path_buf.push(dir_name.into());
         if let Ok(entries) = fs::read_dir(path_buf) {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    if let Some(filename) = entry.file_name().to_str() {
+            for entry in entries.flatten() {
+                // table names can not contain dots, so temporary files never shadow a table
+                if let Some(filename) = entry.file_name().to_str() {
+                    if !filename.ends_with(TEMP_SUFFIX) {
                         files.push(filename.to_string());
                     }
                 }
@@ -63,45 +116,73 @@ impl DiskAccessor {
         Ok(files)
     }
 
-    pub fn write_table_definition(
-        &self,
-        name: &TableName,
-        definition: &TableDefinition,
-    ) -> BEResult<()> {
-        let mut path_buf = PathBuf::from(self.base_path.clone());
-        path_buf.push("meta");
-        path_buf.push(name.0.clone());
-        if path_buf.exists() {
-            return Ok(());
+    /// Names of the tables with a file in `dir`, which is `meta` or `data`.
+    pub fn table_files(&self, dir: &str) -> BEResult<Vec<String>> {
+        self.get_files_in_dir(dir)
+    }
+
+    /// Reads the file of table `name` in `dir`, which is `meta` or `data`.
+    pub fn read_table_file(&self, dir: &str, name: &str) -> BEResult<Vec<u8>> {
+        self.read_file_as_bytes(format!("{dir}/{name}"))
+    }
+
+    /// Reads the database metadata, `None` for databases written before it existed.
+    pub fn read_metadata(&self) -> BEResult<Option<DatabaseMetadata>> {
+        match self.read_file_as_bytes(METADATA_FILE) {
+            Ok(metadata) => Ok(Some(serde_json::from_slice(&metadata)?)),
+            Err(BEErrors::IoError(err)) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
         }
+    }
 
-        let mut file = File::create(path_buf)?;
-        let table_def_str = serde_json::to_string(definition)?;
-        file.write_all(table_def_str.as_bytes())?;
+    /// Starts collecting the changes of a flush, which are only made by
+    /// [`DiskAccessor::commit_flush`].
+    pub fn begin_flush(&self) -> BEResult<Journal> {
+        Journal::create(Path::new(&self.base_path))
+    }
 
-        Ok(())
+    /// Makes all changes collected in `journal` or, if the process dies before the
+    /// journal is durable, none of them.
+    pub fn commit_flush(&self, journal: Journal) -> BEResult<()> {
+        journal.commit()?;
+        journal::recover(Path::new(&self.base_path))
     }
 
-    pub fn write_data_page(
-        &self,
-        name: &TableName,
-        page_num: usize,
-        data: *const u8,
-    ) -> BEResult<()> {
-        let mut path_buf = PathBuf::from(self.base_path.clone());
-        path_buf.push("data");
-        path_buf.push(name.0.clone());
+    /// Whether a flush was interrupted after it was committed, and is only finished by
+    /// [`DiskAccessor::recover`].
+    pub fn has_interrupted_flush(&self) -> bool {
+        journal::is_committed(Path::new(&self.base_path))
+    }
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .read(true)
-            .open(path_buf)?;
+    /// Finishes a flush which was interrupted after it was committed, or discards one
+    /// interrupted before.
+    pub fn recover(&self) -> BEResult<()> {
+        journal::recover(Path::new(&self.base_path))
+    }
+}
+
+/// Replaces the file at `path` with `contents` through a synced temporary file, so it
+/// either has its old contents or the new ones.
+fn write_atomically(path: &Path, contents: &[u8]) -> BEResult<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(TEMP_SUFFIX);
+    let temp_path = path.with_file_name(temp_name);
 
-        file.seek(SeekFrom::Start((page_num * PAGE_SIZE) as u64))?;
+    let mut file = File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(temp_path, path)?;
+    sync_dir(path.parent().unwrap_or(Path::new(".")))
+}
 
-        unsafe { file.write_all(std::slice::from_raw_parts(data, PAGE_SIZE))? }
+/// Makes the creation, removal and renaming of files in `dir` durable.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> BEResult<()> {
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
 
-        Ok(())
-    }
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> BEResult<()> {
+    Ok(())
 }
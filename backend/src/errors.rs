@@ -1,7 +1,7 @@
-use frontend::definitions::table_definition::TableName;
+use frontend::{definitions::table_definition::TableName, ColumnType};
 use thiserror::Error;
 
-pub(crate) type BEResult<T> = Result<T, BEErrors>;
+pub type BEResult<T> = Result<T, BEErrors>;
 #[derive(Debug, Error)]
 pub enum BEErrors {
     #[error("table {0:?} already present in database")]
@@ -10,14 +10,50 @@ pub enum BEErrors {
     InsertFailed,
     #[error("Column {0} not present in table")]
     MissingColumn(String),
+    #[error("Column {0} is given more than once")]
+    DuplicateColumn(String),
     #[error("{2} for Column {0} can not be converted to {1}")]
     MismatchedDataType(String, &'static str, String),
+    #[error("{2} for Column {0} does not fit {1}")]
+    PrecisionExceeded(String, ColumnType, String),
+    #[error("{2} for Column {0} is longer than {1} allows")]
+    ValueTooLong(String, ColumnType, String),
     #[error("Missing table {0}")]
     MissingTable(String),
+    #[error("expected {0} values but got {1}")]
+    ColumnCountMismatch(usize, usize),
+    #[error("invalid expression {0}")]
+    InvalidExpression(String),
     #[error("Error in implementation {0}")]
     InternalError(String),
     #[error("failed because of {0}")]
     IoError(#[from] std::io::Error),
     #[error("failed to serialize {0}")]
     SerializationError(#[from] serde_json::Error),
+    #[error("failed to map row {0}")]
+    MappingError(String),
+    #[error("failed to parse statement {0}")]
+    ParseError(#[from] frontend::errors::DbError),
+    #[error("no value bound for parameter {0}")]
+    UnboundParameter(String),
+    #[error("database is stored in format version {0}, which this build can not read")]
+    UnsupportedFormat(u32),
+    #[error("page size {0} is not a power of two between 4096 and 65536")]
+    InvalidPageSize(usize),
+    #[error("database has pages of {1} bytes, can not open it with pages of {0}")]
+    PageSizeMismatch(usize, usize),
+    #[error("page {1} of table {0} is corrupted")]
+    CorruptedPage(String, usize),
+    #[error("database at {0} is already opened by another process")]
+    DatabaseLocked(String),
+    #[error("database is opened read-only, can not {0}")]
+    ReadOnlyDatabase(String),
+    #[error("table {0} was changed while its rows were read")]
+    TableChanged(String),
+    #[error("invalid schema change, {0}")]
+    InvalidSchemaChange(String),
+    #[error("statement expects {0} parameters but {1} were given")]
+    ParameterCountMismatch(usize, usize),
+    #[error("{2} for parameter {0} can not be converted to {1}")]
+    MismatchedParameterType(usize, &'static str, String),
 }
@@ -1,9 +1,106 @@
-use std::str::FromStr;
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use frontend::TableDefinition;
+use frontend::{command::statement::StatementCommand, TableDefinition};
 use rstest::*;
+use serde::de::DeserializeOwned;
+
+use crate::{errors::BEResult, vm::ExecutionResult, Database, Rows};
+
+static DB_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 #[fixture]
 pub fn student_table_fixture() -> TableDefinition {
     TableDefinition::from_str("create table student(name text, age int);").unwrap()
 }
+
+/// Unique, not yet existing directory to open a test database in, removed again with
+/// everything in it once the test is done.
+#[fixture]
+pub fn temp_db_path() -> TempDbPath {
+    TempDbPath(std::env::temp_dir().join(format!(
+        "simple_db_test_{}_{}",
+        std::process::id(),
+        DB_COUNTER.fetch_add(1, Ordering::SeqCst)
+    )))
+}
+
+pub struct TempDbPath(PathBuf);
+
+impl Deref for TempDbPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDbPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl From<&TempDbPath> for PathBuf {
+    fn from(path: &TempDbPath) -> PathBuf {
+        path.0.clone()
+    }
+}
+
+impl Drop for TempDbPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Database open in a [`TempDbPath`], closed before the directory is removed.
+pub struct TempDatabase {
+    database: Database,
+    _path: TempDbPath,
+}
+
+impl Deref for TempDatabase {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        &self.database
+    }
+}
+
+/// Empty database in a [`temp_db_path`].
+#[fixture]
+pub fn temp_database(temp_db_path: TempDbPath) -> TempDatabase {
+    TempDatabase {
+        database: Database::open(&temp_db_path).unwrap(),
+        _path: temp_db_path,
+    }
+}
+
+/// Database holding an empty `student(id int, name text)` table.
+#[fixture]
+pub fn student_database(temp_database: TempDatabase) -> TempDatabase {
+    run(&temp_database, "create table student(id int, name text);").unwrap();
+    temp_database
+}
+
+/// Parses and runs a single statement.
+pub fn run(database: &Database, statement: &str) -> BEResult<ExecutionResult> {
+    database.execute(StatementCommand::from_str(statement)?)
+}
+
+/// Runs a select and returns its cursor.
+pub fn query(database: &Database, statement: &str) -> BEResult<Rows> {
+    let ExecutionResult::SelectResult(rows) = run(database, statement)? else {
+        panic!("{statement} should return rows");
+    };
+    Ok(rows)
+}
+
+/// Runs a select and maps every row into `T`.
+pub fn select<T: DeserializeOwned>(database: &Database, statement: &str) -> BEResult<Vec<T>> {
+    query(database, statement)?.deserialize().collect()
+}
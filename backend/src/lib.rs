@@ -1,12 +1,12 @@
-use database::Database;
-use lazy_static::lazy_static;
-
 mod database;
 mod disk;
-mod errors;
+pub mod errors;
+#[cfg(test)]
 mod fixtures;
+pub mod mapping;
 pub mod vm;
 
-lazy_static! {
-    static ref DATABASE: Database = Database::default();
-}
+pub use database::{
+    Database, IntegrityProblem, OpenOptions, Parameter, PreparedStatement, Row, Rows,
+};
+pub use errors::{BEErrors, BEResult};
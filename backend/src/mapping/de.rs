@@ -0,0 +1,279 @@
+use frontend::{command::statement::insert::Value, ColumnType, TableDefinition};
+use serde::{
+    de::{
+        value::{SeqDeserializer, StringDeserializer},
+        DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+
+use crate::{
+    database::Row,
+    errors::{BEErrors, BEResult},
+};
+
+/// Deserializes a result row into `T`.
+/// Structs and maps are filled by column name, tuples and sequences by column position.
+pub fn from_row<T: DeserializeOwned>(row: Row, table_definition: &TableDefinition) -> BEResult<T> {
+    let fields = row
+        .into_iter()
+        .enumerate()
+        .map(|(position, value)| {
+            let name = match &value {
+                Value::NamedValue(name, _) | Value::NamedBlob(name, _) => name.clone(),
+                Value::UnnamedValue(_) | Value::UnnamedBlob(_) => {
+                    let Some(column) = table_definition.columns.get(position) else {
+                        return Err(BEErrors::MissingColumn(format!(
+                            "No column at position {position}"
+                        )));
+                    };
+                    column.0.clone()
+                }
+                Value::NamedPlaceholder(_, placeholder)
+                | Value::UnnamedPlaceholder(placeholder) => {
+                    return Err(BEErrors::UnboundParameter(placeholder.to_string()));
+                }
+            };
+            let bytes = match &value {
+                Value::NamedBlob(_, bytes) | Value::UnnamedBlob(bytes) => Some(bytes.clone()),
+                _ => None,
+            };
+            let Some(column) = table_definition.columns.iter().find(|it| it.0 == name) else {
+                return Err(BEErrors::MissingColumn(format!("Column {name} not found")));
+            };
+            Ok(Field {
+                column_type: column.1.clone(),
+                name,
+                value: value.value(),
+                bytes,
+            })
+        })
+        .collect::<BEResult<Vec<_>>>()?;
+
+    T::deserialize(RowDeserializer { fields })
+}
+
+struct Field {
+    name: String,
+    value: String,
+    /// Bytes of a blob value, whose `value` is their hex form.
+    bytes: Option<Vec<u8>>,
+    column_type: ColumnType,
+}
+
+struct RowDeserializer {
+    fields: Vec<Field>,
+}
+
+impl<'de> Deserializer<'de> for RowDeserializer {
+    type Error = BEErrors;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        visitor.visit_map(FieldsAccess {
+            fields: self.fields.into_iter(),
+            pending: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> BEResult<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        visitor.visit_seq(FieldsAccess {
+            fields: self.fields.into_iter(),
+            pending: None,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> BEResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> BEResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct enum identifier ignored_any
+    }
+}
+
+struct FieldsAccess {
+    fields: std::vec::IntoIter<Field>,
+    pending: Option<Field>,
+}
+
+impl<'de> MapAccess<'de> for FieldsAccess {
+    type Error = BEErrors;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> BEResult<Option<K::Value>> {
+        let Some(field) = self.fields.next() else {
+            return Ok(None);
+        };
+        let key: StringDeserializer<BEErrors> = field.name.clone().into_deserializer();
+        self.pending = Some(field);
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> BEResult<V::Value> {
+        let Some(field) = self.pending.take() else {
+            return Err(BEErrors::InternalError(String::from(
+                "value requested before key",
+            )));
+        };
+        seed.deserialize(FieldDeserializer(field))
+    }
+}
+
+impl<'de> SeqAccess<'de> for FieldsAccess {
+    type Error = BEErrors;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> BEResult<Option<T::Value>> {
+        self.fields
+            .next()
+            .map(|field| seed.deserialize(FieldDeserializer(field)))
+            .transpose()
+    }
+}
+
+struct FieldDeserializer(Field);
+
+impl FieldDeserializer {
+    fn parse<T: std::str::FromStr>(&self, type_name: &'static str) -> BEResult<T> {
+        self.0.value.parse::<T>().map_err(|_| {
+            BEErrors::MappingError(format!(
+                "{} for Column {} can not be converted to {type_name}",
+                self.0.value, self.0.name
+            ))
+        })
+    }
+    fn blob(&self) -> BEResult<Vec<u8>> {
+        self.0.bytes.clone().ok_or_else(|| {
+            BEErrors::MappingError(format!(
+                "{} for Column {} is not a blob",
+                self.0.value, self.0.name
+            ))
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+                visitor.$visit(self.parse::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for FieldDeserializer {
+    type Error = BEErrors;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        match self.0.column_type {
+            ColumnType::Int => visitor.visit_i64(self.parse::<i64>("i64")?),
+            ColumnType::Text | ColumnType::Varchar(_) | ColumnType::Char(_) => {
+                visitor.visit_string(self.0.value)
+            }
+            ColumnType::Real => visitor.visit_f64(self.parse::<f64>("f64")?),
+            ColumnType::Bool => visitor.visit_bool(self.parse::<bool>("bool")?),
+            // decimals are handed out as text to stay exact, unless asked for a number
+            ColumnType::Date
+            | ColumnType::Time
+            | ColumnType::Timestamp
+            | ColumnType::Decimal(..) => visitor.visit_string(self.0.value),
+            ColumnType::Blob => self.deserialize_byte_buf(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        visitor.visit_string(self.0.value)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        visitor.visit_string(self.0.value)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    // blobs hand out their bytes, any other column its text
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        if self.0.column_type != ColumnType::Blob {
+            return visitor.visit_byte_buf(self.0.value.into_bytes());
+        }
+        visitor.visit_byte_buf(self.blob()?)
+    }
+
+    // lets blobs map to `Vec<u8>`, which asks for a sequence rather than bytes
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        if self.0.column_type != ColumnType::Blob {
+            return self.deserialize_any(visitor);
+        }
+        visitor.visit_seq(SeqDeserializer::new(self.blob()?.into_iter()))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> BEResult<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> BEResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> BEResult<V::Value> {
+        let variant: StringDeserializer<BEErrors> = self.0.value.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    forward_to_deserialize_any! {
+        unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
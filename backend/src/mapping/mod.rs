@@ -0,0 +1,75 @@
+//! Conversion between result rows / insert values and user types through serde.
+//!
+//! Rows are mapped by column name, so a struct deserialized from a row needs one field
+//! per selected column and a struct inserted into a table needs one field per column.
+mod de;
+mod ser;
+
+pub use de::from_row;
+pub use ser::to_values;
+
+use std::fmt::Display;
+
+use crate::errors::BEErrors;
+
+impl serde::de::Error for BEErrors {
+    fn custom<T: Display>(msg: T) -> Self {
+        BEErrors::MappingError(msg.to_string())
+    }
+}
+
+impl serde::ser::Error for BEErrors {
+    fn custom<T: Display>(msg: T) -> Self {
+        BEErrors::MappingError(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use frontend::{command::statement::insert::Value, TableDefinition};
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{errors::BEResult, fixtures::student_table_fixture};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Student {
+        age: u8,
+        name: String,
+    }
+
+    #[rstest]
+    fn test_round_trip(student_table_fixture: TableDefinition) -> BEResult<()> {
+        let student = Student {
+            age: 22,
+            name: String::from("harry potter"),
+        };
+
+        let values = to_values(&student)?;
+        assert_eq!(
+            vec![
+                Value::NamedValue(String::from("age"), String::from("22")),
+                Value::NamedValue(String::from("name"), String::from("harry potter")),
+            ],
+            values
+        );
+
+        assert_eq!(student, from_row(values, &student_table_fixture)?);
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_tuple_and_type_mismatch(student_table_fixture: TableDefinition) -> BEResult<()> {
+        let row = vec![
+            Value::NamedValue(String::from("name"), String::from("john")),
+            Value::NamedValue(String::from("age"), String::from("21")),
+        ];
+        let (name, age): (String, i64) = from_row(row.clone(), &student_table_fixture)?;
+        assert_eq!((String::from("john"), 21), (name, age));
+
+        let mismatch = from_row::<(i64, i64)>(row, &student_table_fixture);
+        assert!(matches!(mismatch, Err(BEErrors::MappingError(_))));
+        Ok(())
+    }
+}
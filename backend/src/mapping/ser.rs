@@ -0,0 +1,362 @@
+use frontend::command::statement::insert::Value;
+use serde::{
+    ser::{
+        Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple,
+        SerializeTupleStruct,
+    },
+    Serialize, Serializer,
+};
+
+use crate::errors::{BEErrors, BEResult};
+
+/// Serializes `record` into insert values.
+/// Structs and maps produce named values, tuples and sequences produce positional values.
+pub fn to_values<T: Serialize + ?Sized>(record: &T) -> BEResult<Vec<Value>> {
+    record.serialize(RowSerializer)
+}
+
+fn unsupported(kind: &str) -> BEErrors {
+    BEErrors::MappingError(format!("{kind} can not be mapped to a row"))
+}
+
+struct RowSerializer;
+
+struct ValuesCollector {
+    values: Vec<Value>,
+    pending_key: Option<String>,
+}
+
+impl ValuesCollector {
+    fn new(len: Option<usize>) -> Self {
+        ValuesCollector {
+            values: Vec::with_capacity(len.unwrap_or_default()),
+            pending_key: None,
+        }
+    }
+}
+
+macro_rules! unsupported_row {
+    ($($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> BEResult<Vec<Value>> {
+                Err(unsupported(stringify!($method)))
+            }
+        )*
+    };
+}
+
+impl Serializer for RowSerializer {
+    type Ok = Vec<Value>;
+    type Error = BEErrors;
+    type SerializeSeq = ValuesCollector;
+    type SerializeTuple = ValuesCollector;
+    type SerializeTupleStruct = ValuesCollector;
+    type SerializeTupleVariant = Impossible<Vec<Value>, BEErrors>;
+    type SerializeMap = ValuesCollector;
+    type SerializeStruct = ValuesCollector;
+    type SerializeStructVariant = Impossible<Vec<Value>, BEErrors>;
+
+    unsupported_row! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> BEResult<Vec<Value>> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> BEResult<Vec<Value>> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> BEResult<Vec<Value>> {
+        Err(unsupported("enum variant"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> BEResult<ValuesCollector> {
+        Ok(ValuesCollector::new(len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> BEResult<ValuesCollector> {
+        Ok(ValuesCollector::new(Some(len)))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> BEResult<ValuesCollector> {
+        Ok(ValuesCollector::new(Some(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> BEResult<Self::SerializeTupleVariant> {
+        Err(unsupported("enum variant"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> BEResult<ValuesCollector> {
+        Ok(ValuesCollector::new(len))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> BEResult<ValuesCollector> {
+        Ok(ValuesCollector::new(Some(len)))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> BEResult<Self::SerializeStructVariant> {
+        Err(unsupported("enum variant"))
+    }
+}
+
+impl SerializeSeq for ValuesCollector {
+    type Ok = Vec<Value>;
+    type Error = BEErrors;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> BEResult<()> {
+        self.values
+            .push(Value::UnnamedValue(value.serialize(FieldSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> BEResult<Vec<Value>> {
+        Ok(self.values)
+    }
+}
+
+impl SerializeTuple for ValuesCollector {
+    type Ok = Vec<Value>;
+    type Error = BEErrors;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> BEResult<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> BEResult<Vec<Value>> {
+        Ok(self.values)
+    }
+}
+
+impl SerializeTupleStruct for ValuesCollector {
+    type Ok = Vec<Value>;
+    type Error = BEErrors;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> BEResult<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> BEResult<Vec<Value>> {
+        Ok(self.values)
+    }
+}
+
+impl SerializeMap for ValuesCollector {
+    type Ok = Vec<Value>;
+    type Error = BEErrors;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> BEResult<()> {
+        self.pending_key = Some(key.serialize(FieldSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> BEResult<()> {
+        let Some(key) = self.pending_key.take() else {
+            return Err(BEErrors::InternalError(String::from(
+                "value serialized before key",
+            )));
+        };
+        self.values
+            .push(Value::NamedValue(key, value.serialize(FieldSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> BEResult<Vec<Value>> {
+        Ok(self.values)
+    }
+}
+
+impl SerializeStruct for ValuesCollector {
+    type Ok = Vec<Value>;
+    type Error = BEErrors;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> BEResult<()> {
+        self.values.push(Value::NamedValue(
+            String::from(key),
+            value.serialize(FieldSerializer)?,
+        ));
+        Ok(())
+    }
+
+    fn end(self) -> BEResult<Vec<Value>> {
+        Ok(self.values)
+    }
+}
+
+/// Serializes a single column value into the textual form the storage layer parses.
+struct FieldSerializer;
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, value: $ty) -> BEResult<String> {
+                Ok(value.to_string())
+            }
+        )*
+    };
+}
+
+impl Serializer for FieldSerializer {
+    type Ok = String;
+    type Error = BEErrors;
+    type SerializeSeq = Impossible<String, BEErrors>;
+    type SerializeTuple = Impossible<String, BEErrors>;
+    type SerializeTupleStruct = Impossible<String, BEErrors>;
+    type SerializeTupleVariant = Impossible<String, BEErrors>;
+    type SerializeMap = Impossible<String, BEErrors>;
+    type SerializeStruct = Impossible<String, BEErrors>;
+    type SerializeStructVariant = Impossible<String, BEErrors>;
+
+    serialize_display! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> BEResult<String> {
+        String::from_utf8(value.to_vec()).map_err(|_| unsupported("non utf-8 bytes"))
+    }
+
+    fn serialize_none(self) -> BEResult<String> {
+        Err(unsupported("missing value"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> BEResult<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> BEResult<String> {
+        Err(unsupported("unit value"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> BEResult<String> {
+        Err(unsupported("unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> BEResult<String> {
+        Ok(String::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> BEResult<String> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> BEResult<String> {
+        Err(unsupported("enum variant with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> BEResult<Self::SerializeSeq> {
+        Err(unsupported("nested sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> BEResult<Self::SerializeTuple> {
+        Err(unsupported("nested tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> BEResult<Self::SerializeTupleStruct> {
+        Err(unsupported("nested tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> BEResult<Self::SerializeTupleVariant> {
+        Err(unsupported("enum variant with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> BEResult<Self::SerializeMap> {
+        Err(unsupported("nested map"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> BEResult<Self::SerializeStruct> {
+        Err(unsupported("nested struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> BEResult<Self::SerializeStructVariant> {
+        Err(unsupported("enum variant with data"))
+    }
+}
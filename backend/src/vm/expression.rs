@@ -0,0 +1,380 @@
+use std::cmp::Ordering;
+
+use frontend::{
+    command::statement::{
+        expression::{ArithmeticOperator, ComparisonOperator, Expression},
+        insert::Parameter,
+    },
+    definitions::table_definition::TableName,
+    Column, ColumnType, TableDefinition,
+};
+
+use crate::{
+    database::{pad_char, Decimal, Field, Interval},
+    errors::{BEErrors, BEResult},
+};
+
+use super::temporal;
+
+/// Value an expression evaluates to.
+/// Literals stay untyped until they meet a column, whose type they are parsed as,
+/// or a computed value, which they are read as a number or text for.
+enum Operand<'a> {
+    Typed(&'a Column, Field),
+    Computed(Field),
+    Untyped(String),
+}
+
+/// Evaluates `filter` against a record decoded with `table_definition`.
+/// The filter has to evaluate to a bool, like a comparison or a bool column does.
+pub(crate) fn evaluate_predicate(
+    filter: &Expression,
+    record: &[Field],
+    table_definition: &TableDefinition,
+) -> BEResult<bool> {
+    let value = evaluate(filter, record, table_definition)?;
+    into_bool(value, filter)
+}
+
+/// Evaluates the default of `column` into the value stored for rows lacking one,
+/// `None` when the column has no default. Defaults refer to no columns, so they are
+/// evaluated against an empty record.
+pub(crate) fn evaluate_default(column: &Column) -> BEResult<Option<Field>> {
+    let Some(expression) = column.default_expression()? else {
+        return Ok(None);
+    };
+    let no_columns = TableDefinition {
+        name: TableName(column.0.clone()),
+        columns: Vec::new(),
+        schema_history: Vec::new(),
+    };
+    let value = match evaluate(&expression, &[], &no_columns)? {
+        Operand::Computed(Field::Blob(bytes)) => return Field::from_bytes(column, bytes).map(Some),
+        Operand::Untyped(literal) => literal,
+        Operand::Typed(_, field) | Operand::Computed(field) => field.to_string(),
+    };
+    Field::parse(column, value).map(Some)
+}
+
+fn evaluate<'a>(
+    expression: &Expression,
+    record: &[Field],
+    table_definition: &'a TableDefinition,
+) -> BEResult<Operand<'a>> {
+    match expression {
+        Expression::Column(name) => {
+            let Some(index) = table_definition
+                .columns
+                .iter()
+                .position(|it| it.0 == name.0)
+            else {
+                return Err(BEErrors::MissingColumn(format!(
+                    "Column {} not found",
+                    name.0
+                )));
+            };
+            Ok(Operand::Typed(
+                &table_definition.columns[index],
+                record[index].clone(),
+            ))
+        }
+        Expression::Literal(value) => Ok(Operand::Untyped(value.clone())),
+        Expression::Blob(bytes) => Ok(Operand::Computed(Field::Blob(bytes.clone()))),
+        // text is parsed by what it meets, like literals are
+        Expression::Bound(Parameter::Text(text)) => Ok(Operand::Untyped(text.clone())),
+        Expression::Bound(Parameter::Int(value)) => Ok(Operand::Computed(Field::Int(*value))),
+        Expression::Bound(Parameter::Real(value)) => Ok(Operand::Computed(Field::Real(*value))),
+        Expression::Bound(Parameter::Bool(value)) => Ok(Operand::Computed(Field::Bool(*value))),
+        Expression::Bound(Parameter::Blob(bytes)) => {
+            Ok(Operand::Computed(Field::Blob(bytes.clone())))
+        }
+        Expression::Placeholder(placeholder) => {
+            Err(BEErrors::UnboundParameter(placeholder.to_string()))
+        }
+        Expression::Interval(value) => {
+            Ok(Operand::Computed(Field::Interval(Interval::parse(value)?)))
+        }
+        Expression::Function(name, arguments) => {
+            let arguments = arguments
+                .iter()
+                .map(|it| {
+                    evaluate(it, record, table_definition).map(|operand| match operand {
+                        Operand::Typed(_, field) | Operand::Computed(field) => field,
+                        Operand::Untyped(literal) => Field::Text(literal),
+                    })
+                })
+                .collect::<BEResult<Vec<_>>>()?;
+            Ok(Operand::Computed(temporal::call(name, arguments)?))
+        }
+        Expression::Arithmetic(left, operator, right) => {
+            let left = evaluate(left, record, table_definition)?;
+            let right = evaluate(right, record, table_definition)?;
+            Ok(Operand::Computed(calculate(left, *operator, right)?))
+        }
+        Expression::Comparison(left, operator, right) => {
+            let left = evaluate(left, record, table_definition)?;
+            let right = evaluate(right, record, table_definition)?;
+            let matches = matches_ordering(*operator, compare(left, right)?);
+            Ok(Operand::Computed(Field::Bool(matches)))
+        }
+        Expression::And(left, right) => {
+            let value = evaluate_predicate(left, record, table_definition)?
+                && evaluate_predicate(right, record, table_definition)?;
+            Ok(Operand::Computed(Field::Bool(value)))
+        }
+        Expression::Or(left, right) => {
+            let value = evaluate_predicate(left, record, table_definition)?
+                || evaluate_predicate(right, record, table_definition)?;
+            Ok(Operand::Computed(Field::Bool(value)))
+        }
+        Expression::Not(inner) => {
+            let value = !evaluate_predicate(inner, record, table_definition)?;
+            Ok(Operand::Computed(Field::Bool(value)))
+        }
+    }
+}
+
+fn into_bool(operand: Operand, expression: &Expression) -> BEResult<bool> {
+    let value = match operand {
+        Operand::Typed(_, Field::Bool(value)) | Operand::Computed(Field::Bool(value)) => {
+            Some(value)
+        }
+        Operand::Untyped(literal) => Field::parse_bool(&literal),
+        _ => None,
+    };
+    value.ok_or_else(|| BEErrors::InvalidExpression(format!("{expression:?} is not a condition")))
+}
+
+/// Parses a literal met with `column`, promoting it to real when an int column is
+/// compared with a fractional number. Decimal literals keep all their digits, so that
+/// they are not rounded to the scale of the column they are compared with.
+fn parse_literal(column: &Column, literal: String) -> BEResult<Field> {
+    // a literal longer than the column simply compares unequal, shorter ones are padded
+    // like the values of char columns are
+    match column.1 {
+        ColumnType::Varchar(_) => return Ok(Field::Text(literal)),
+        ColumnType::Char(length) => return Ok(Field::Text(pad_char(literal, length))),
+        _ => {}
+    }
+    if let ColumnType::Decimal(..) = column.1 {
+        return match Decimal::parse(&literal) {
+            Some(decimal) => Ok(Field::Decimal(decimal)),
+            None => Err(BEErrors::MismatchedDataType(
+                column.0.clone(),
+                "Decimal",
+                literal,
+            )),
+        };
+    }
+    match Field::parse(column, literal) {
+        Err(BEErrors::MismatchedDataType(name, type_name, literal))
+            if column.1 == ColumnType::Int =>
+        {
+            match literal.parse::<f64>() {
+                Ok(value) if value.is_finite() => Ok(Field::Real(value)),
+                _ => Err(BEErrors::MismatchedDataType(name, type_name, literal)),
+            }
+        }
+        result => result,
+    }
+}
+
+fn parse_number(literal: &str) -> BEResult<Field> {
+    if let Ok(value) = literal.parse::<i64>() {
+        return Ok(Field::Int(value));
+    }
+    match literal.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(Field::Real(value)),
+        _ => Err(BEErrors::InvalidExpression(format!(
+            "{literal} is not a number"
+        ))),
+    }
+}
+
+/// Parses a literal met with a computed value as a value of the same kind.
+fn parse_like(field: &Field, literal: String) -> BEResult<Field> {
+    match field {
+        Field::Bool(_) => Field::parse_bool(&literal)
+            .map(Field::Bool)
+            .ok_or_else(|| BEErrors::InvalidExpression(format!("{literal} is not a bool"))),
+        Field::Text(_) => Ok(Field::Text(literal)),
+        Field::Blob(_) => Err(BEErrors::InvalidExpression(format!(
+            "{literal} is not a blob"
+        ))),
+        Field::Decimal(_) => match Decimal::parse(&literal) {
+            Some(decimal) => Ok(Field::Decimal(decimal)),
+            None => parse_number(&literal),
+        },
+        Field::Date(_) | Field::Time(_) | Field::Timestamp(_) | Field::Interval(_) => {
+            temporal::parse_temporal(&literal).ok_or_else(|| {
+                BEErrors::InvalidExpression(format!("{literal} is not a date, time or timestamp"))
+            })
+        }
+        _ => parse_number(&literal),
+    }
+}
+
+/// Resolves the operands of arithmetic, where untyped literals are numbers unless the
+/// other side calls for something else, like a timestamp being moved by an interval
+/// or a decimal, next to which fractions stay exact.
+fn arithmetic_operands(left: Operand, right: Operand) -> BEResult<(Field, Field)> {
+    let resolve = |literal: String, other: &Field| match other {
+        Field::Decimal(_) => parse_like(other, literal),
+        _ => parse_number(&literal).or_else(|_| parse_like(other, literal)),
+    };
+    match (left, right) {
+        (
+            Operand::Typed(_, left) | Operand::Computed(left),
+            Operand::Typed(_, right) | Operand::Computed(right),
+        ) => Ok((left, right)),
+        (Operand::Typed(_, left) | Operand::Computed(left), Operand::Untyped(right)) => {
+            let right = resolve(right, &left)?;
+            Ok((left, right))
+        }
+        (Operand::Untyped(left), Operand::Typed(_, right) | Operand::Computed(right)) => {
+            Ok((resolve(left, &right)?, right))
+        }
+        (Operand::Untyped(left), Operand::Untyped(right)) => {
+            Ok((parse_number(&left)?, parse_number(&right)?))
+        }
+    }
+}
+
+/// Applies `operator`, staying in integers when both sides are ints, in decimals when
+/// decimals meet ints or decimals and promoting to real otherwise.
+fn calculate(left: Operand, operator: ArithmeticOperator, right: Operand) -> BEResult<Field> {
+    let (left, right) = arithmetic_operands(left, right)?;
+    let has_decimal = matches!(left, Field::Decimal(_)) || matches!(right, Field::Decimal(_));
+    if let (true, Some(left), Some(right)) = (has_decimal, as_decimal(&left), as_decimal(&right)) {
+        let value = match operator {
+            ArithmeticOperator::Add => left.checked_add(right),
+            ArithmeticOperator::Subtract => left.checked_sub(right),
+            ArithmeticOperator::Multiply => left.checked_mul(right),
+            ArithmeticOperator::Divide if right.value == 0 => {
+                return Err(BEErrors::InvalidExpression(format!(
+                    "{left} can not be divided by zero"
+                )))
+            }
+            ArithmeticOperator::Divide => left.checked_div(right),
+        };
+        return value.map(Field::Decimal).ok_or_else(|| {
+            BEErrors::InvalidExpression(format!("{left} {operator:?} {right} overflows"))
+        });
+    }
+    match (&left, &right) {
+        (Field::Int(left), Field::Int(right)) => {
+            let value = match operator {
+                ArithmeticOperator::Add => left.checked_add(*right),
+                ArithmeticOperator::Subtract => left.checked_sub(*right),
+                ArithmeticOperator::Multiply => left.checked_mul(*right),
+                ArithmeticOperator::Divide if *right == 0 => {
+                    return Err(BEErrors::InvalidExpression(format!(
+                        "{left} can not be divided by zero"
+                    )))
+                }
+                ArithmeticOperator::Divide => left.checked_div(*right),
+            };
+            value.map(Field::Int).ok_or_else(|| {
+                BEErrors::InvalidExpression(format!("{left} {operator:?} {right} overflows"))
+            })
+        }
+        _ => {
+            let (Some(left), Some(right)) = (as_real(&left), as_real(&right)) else {
+                return temporal::calculate(left, operator, right);
+            };
+            let value = match operator {
+                ArithmeticOperator::Add => left + right,
+                ArithmeticOperator::Subtract => left - right,
+                ArithmeticOperator::Multiply => left * right,
+                ArithmeticOperator::Divide => left / right,
+            };
+            // like ints, reals fail rather than turn into infinities or nan
+            if !value.is_finite() {
+                return Err(BEErrors::InvalidExpression(format!(
+                    "{left} {operator:?} {right} is not a finite number"
+                )));
+            }
+            Ok(Field::Real(value))
+        }
+    }
+}
+
+fn as_real(field: &Field) -> Option<f64> {
+    match field {
+        Field::Int(value) => Some(*value as f64),
+        Field::Real(value) => Some(*value),
+        Field::Decimal(value) => Some(value.to_f64()),
+        _ => None,
+    }
+}
+
+/// Reads a decimal, or an int next to one, as a decimal.
+fn as_decimal(field: &Field) -> Option<Decimal> {
+    match field {
+        Field::Int(value) => Some(Decimal::from_int(*value)),
+        Field::Decimal(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn compare(left: Operand, right: Operand) -> BEResult<Ordering> {
+    let (left, right) = match (left, right) {
+        (
+            Operand::Typed(_, left) | Operand::Computed(left),
+            Operand::Typed(_, right) | Operand::Computed(right),
+        ) => (left, right),
+        (Operand::Typed(column, left), Operand::Untyped(right)) => {
+            (left, parse_literal(column, right)?)
+        }
+        (Operand::Untyped(left), Operand::Typed(column, right)) => {
+            (parse_literal(column, left)?, right)
+        }
+        (Operand::Computed(left), Operand::Untyped(right)) => {
+            let right = parse_like(&left, right)?;
+            (left, right)
+        }
+        (Operand::Untyped(left), Operand::Computed(right)) => (parse_like(&right, left)?, right),
+        (Operand::Untyped(left), Operand::Untyped(right)) => {
+            match (parse_number(&left), parse_number(&right)) {
+                (Ok(left), Ok(right)) => (left, right),
+                _ => (Field::Text(left), Field::Text(right)),
+            }
+        }
+    };
+
+    let ordering = match (&left, &right) {
+        (Field::Int(left), Field::Int(right)) => Some(left.cmp(right)),
+        (Field::Text(left), Field::Text(right)) => Some(left.cmp(right)),
+        (Field::Bool(left), Field::Bool(right)) => Some(left.cmp(right)),
+        (Field::Blob(left), Field::Blob(right)) => Some(left.cmp(right)),
+        (Field::Time(left), Field::Time(right)) => Some(left.cmp(right)),
+        (Field::Decimal(_), Field::Decimal(_) | Field::Int(_))
+        | (Field::Int(_), Field::Decimal(_)) => as_decimal(&left)
+            .zip(as_decimal(&right))
+            .map(|(left, right)| left.cmp(&right)),
+        (Field::Interval(left), Field::Interval(right)) => {
+            Some(left.approximate_micros().cmp(&right.approximate_micros()))
+        }
+        (Field::Date(_) | Field::Timestamp(_), Field::Date(_) | Field::Timestamp(_)) => {
+            Some(temporal::as_timestamp(&left)?.cmp(&temporal::as_timestamp(&right)?))
+        }
+        _ => match (as_real(&left), as_real(&right)) {
+            (Some(left), Some(right)) => left.partial_cmp(&right),
+            _ => None,
+        },
+    };
+    ordering.ok_or_else(|| {
+        BEErrors::InvalidExpression(format!("{left:?} can not be compared with {right:?}"))
+    })
+}
+
+fn matches_ordering(operator: ComparisonOperator, ordering: Ordering) -> bool {
+    match operator {
+        ComparisonOperator::Eq => ordering.is_eq(),
+        ComparisonOperator::NotEq => ordering.is_ne(),
+        ComparisonOperator::Lt => ordering.is_lt(),
+        ComparisonOperator::LtEq => ordering.is_le(),
+        ComparisonOperator::Gt => ordering.is_gt(),
+        ComparisonOperator::GtEq => ordering.is_ge(),
+    }
+}
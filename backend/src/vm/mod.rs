@@ -1,84 +1,155 @@
 use std::fmt::Debug;
 
 use frontend::command::statement::StatementCommand;
-use prettytable::{Cell, Row};
 use tracing::instrument;
 
-use crate::{errors::BEResult, DATABASE};
+pub(crate) mod expression;
+mod temporal;
+
+use crate::{
+    database::{Database, Rows},
+    errors::{BEErrors, BEResult},
+};
 pub trait Execution {
     type Output;
-    fn execute(self) -> BEResult<Self::Output>;
+    fn execute(self, database: &Database) -> BEResult<Self::Output>;
 }
 
 #[derive(Debug, Clone)]
 pub enum ExecutionResult {
-    SelectResult(()),
+    SelectResult(Rows),
     InsertResult(()),
     CreateResult(()),
+    DropResult(()),
+    TruncateResult(()),
+    AlterResult(()),
+    VacuumResult(()),
 }
 
-#[instrument]
-pub fn execute(command: StatementCommand) -> BEResult<ExecutionResult> {
+#[instrument(skip(database))]
+pub fn execute(database: &Database, command: StatementCommand) -> BEResult<ExecutionResult> {
+    if database.is_read_only() {
+        let kind = match command {
+            StatementCommand::Select(_) => None,
+            StatementCommand::Insert(_) | StatementCommand::InsertSelect(_) => Some("insert"),
+            StatementCommand::Create(_) | StatementCommand::CreateAs(_) => Some("create"),
+            StatementCommand::Drop(_) => Some("drop"),
+            StatementCommand::Truncate(_) => Some("truncate"),
+            StatementCommand::Alter(_) => Some("alter"),
+            StatementCommand::Vacuum(_) => Some("vacuum"),
+        };
+        if let Some(kind) = kind {
+            return Err(BEErrors::ReadOnlyDatabase(format!("execute {kind}")));
+        }
+    }
     match command {
         StatementCommand::Select(statement) => statement
-            .execute()
-            .map(|_it| ExecutionResult::SelectResult(())),
+            .execute(database)
+            .map(ExecutionResult::SelectResult),
         StatementCommand::Insert(statement) => statement
-            .execute()
+            .execute(database)
+            .map(|_it| ExecutionResult::InsertResult(())),
+        StatementCommand::InsertSelect(statement) => statement
+            .execute(database)
             .map(|_it| ExecutionResult::InsertResult(())),
         StatementCommand::Create(statement) => statement
-            .execute()
+            .execute(database)
+            .map(|_it| ExecutionResult::CreateResult(())),
+        StatementCommand::CreateAs(statement) => statement
+            .execute(database)
             .map(|_it| ExecutionResult::CreateResult(())),
+        StatementCommand::Drop(statement) => statement
+            .execute(database)
+            .map(|_it| ExecutionResult::DropResult(())),
+        StatementCommand::Truncate(statement) => statement
+            .execute(database)
+            .map(|_it| ExecutionResult::TruncateResult(())),
+        StatementCommand::Alter(statement) => statement
+            .execute(database)
+            .map(|_it| ExecutionResult::AlterResult(())),
+        StatementCommand::Vacuum(statement) => statement
+            .execute(database)
+            .map(|_it| ExecutionResult::VacuumResult(())),
     }
 }
 
-pub fn initialize_db(db_path: frontend::DbPath) -> BEResult<()> {
-    DATABASE.init_db_with_file(db_path.0)
+impl Execution for frontend::InsertStatement {
+    type Output = ();
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
+        tracing::info!("");
+        database.insert_record(self)
+    }
 }
 
-pub fn persist_to_db() -> BEResult<()> {
-    DATABASE.flush_db()
+impl Execution for frontend::InsertSelectStatement {
+    type Output = ();
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
+        tracing::info!("");
+        database.insert_selected_records(self)
+    }
 }
 
-impl Execution for frontend::InsertStatement {
+impl Execution for frontend::SelectStatement {
+    type Output = Rows;
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<Rows> {
+        tracing::info!("");
+        database.select_records(self)
+    }
+}
+
+impl Execution for frontend::CreateTableStatement {
     type Output = ();
-    #[instrument]
-    fn execute(self) -> BEResult<()> {
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
         tracing::info!("");
-        DATABASE.insert_record(self)
+        database.create_table(self)
     }
 }
 
-impl Execution for frontend::SelectStatement {
+impl Execution for frontend::CreateTableAsStatement {
     type Output = ();
-    #[instrument]
-    fn execute(self) -> BEResult<()> {
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
         tracing::info!("");
-        use prettytable::Table;
-        let mut table = Table::new();
-        table.add_row(Row::new(
-            self.1.iter().map(|it| Cell::new(&it.0)).collect::<Vec<_>>(),
-        ));
-        let rows = DATABASE.select_records(self)?;
-        for row in rows {
-            table.add_row(Row::new(
-                row.into_iter().map(|it| Cell::new(&it.value())).collect(),
-            ));
-        }
+        database.create_table_as(self)
+    }
+}
 
-        table.printstd();
-        Ok(())
+impl Execution for frontend::DropTableStatement {
+    type Output = ();
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
+        tracing::info!("");
+        database.drop_table(self)
+    }
+}
+
+impl Execution for frontend::TruncateTableStatement {
+    type Output = ();
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
+        tracing::info!("");
+        database.truncate_table(self)
+    }
+}
+
+impl Execution for frontend::AlterTableStatement {
+    type Output = ();
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
+        tracing::info!("");
+        database.alter_table(self)
     }
 }
 
-impl Execution for frontend::TableDefinition {
+impl Execution for frontend::VacuumStatement {
     type Output = ();
-    #[instrument]
-    fn execute(self) -> BEResult<()> {
+    #[instrument(skip(database))]
+    fn execute(self, database: &Database) -> BEResult<()> {
         tracing::info!("");
-        println!("Table def");
-        println!("{self}");
-        DATABASE.add_table_definitions(self)?;
-        Ok(())
+        database.vacuum(self)
     }
 }
@@ -0,0 +1,198 @@
+use chrono::{Datelike, Months, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Utc};
+use frontend::command::statement::expression::ArithmeticOperator;
+
+use crate::{
+    database::{truncate_to_micros, Field, Interval},
+    errors::{BEErrors, BEResult},
+};
+
+/// Parses a literal used next to a date, time or timestamp as the most specific of them.
+pub(crate) fn parse_temporal(literal: &str) -> Option<Field> {
+    Field::parse_date(literal)
+        .map(Field::Date)
+        .or_else(|| Field::parse_timestamp(literal).map(Field::Timestamp))
+        .or_else(|| Field::parse_time(literal).map(Field::Time))
+}
+
+/// Calls the built in function `name`, untyped literal arguments are passed as text.
+pub(crate) fn call(name: &str, arguments: Vec<Field>) -> BEResult<Field> {
+    match (name, arguments.as_slice()) {
+        ("now" | "current_timestamp", []) => {
+            Ok(Field::Timestamp(truncate_to_micros(Utc::now().naive_utc())))
+        }
+        ("current_date", []) => Ok(Field::Date(Utc::now().date_naive())),
+        ("current_time", []) => Ok(Field::Time(truncate_to_micros(Utc::now().time()))),
+        ("date_part", [Field::Text(part), source]) => date_part(part, source),
+        _ => Err(BEErrors::InvalidExpression(format!(
+            "no function {name} taking {} arguments",
+            arguments.len()
+        ))),
+    }
+}
+
+/// Extracts `part`, like `year` or `hour`, from a date, time, timestamp or interval.
+fn date_part(part: &str, source: &Field) -> BEResult<Field> {
+    let source = match source {
+        Field::Text(literal) => parse_temporal(literal).ok_or_else(|| {
+            BEErrors::InvalidExpression(format!("{literal} is not a date, time or timestamp"))
+        })?,
+        source => source.clone(),
+    };
+    let unsupported = || BEErrors::InvalidExpression(format!("can not take {part} of {source:?}"));
+
+    let part = part.to_lowercase();
+    let value = match &source {
+        Field::Date(date) => timestamp_part(&part, date.and_time(NaiveTime::MIN)),
+        Field::Timestamp(timestamp) => timestamp_part(&part, *timestamp),
+        Field::Time(time) => time_part(&part, *time),
+        Field::Interval(interval) => interval_part(&part, interval),
+        _ => None,
+    };
+    value.map(Field::Int).ok_or_else(unsupported)
+}
+
+fn timestamp_part(part: &str, timestamp: NaiveDateTime) -> Option<i64> {
+    let value = match part {
+        "year" => timestamp.year() as i64,
+        "quarter" => (timestamp.month0() / 3 + 1) as i64,
+        "month" => timestamp.month() as i64,
+        "week" => timestamp.iso_week().week() as i64,
+        "day" => timestamp.day() as i64,
+        "dow" => timestamp.weekday().num_days_from_sunday() as i64,
+        "doy" => timestamp.ordinal() as i64,
+        "epoch" => timestamp.and_utc().timestamp(),
+        _ => return time_part(part, timestamp.time()),
+    };
+    Some(value)
+}
+
+fn time_part(part: &str, time: NaiveTime) -> Option<i64> {
+    let value = match part {
+        "hour" => time.hour() as i64,
+        "minute" => time.minute() as i64,
+        "second" => time.second() as i64,
+        "microseconds" => Field::time_to_micros(time) % 60_000_000,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn interval_part(part: &str, interval: &Interval) -> Option<i64> {
+    let time = TimeDelta::microseconds(interval.micros);
+    let value = match part {
+        "year" => interval.months as i64 / 12,
+        "month" => interval.months as i64 % 12,
+        "day" => time.num_days(),
+        "hour" => time.num_hours() % 24,
+        "minute" => time.num_minutes() % 60,
+        "second" => time.num_seconds() % 60,
+        "epoch" => interval.months as i64 * 30 * 86_400 + time.num_seconds(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Arithmetic involving dates, times, timestamps and intervals.
+/// Dates move by whole days when combined with ints, and turn into timestamps
+/// when combined with intervals.
+pub(crate) fn calculate(
+    left: Field,
+    operator: ArithmeticOperator,
+    right: Field,
+) -> BEResult<Field> {
+    use ArithmeticOperator::*;
+
+    let overflow = || BEErrors::InvalidExpression(String::from("date arithmetic overflows"));
+    match (left, operator, right) {
+        (Field::Date(date), Add, Field::Int(days)) | (Field::Int(days), Add, Field::Date(date)) => {
+            date.checked_add_signed(TimeDelta::try_days(days).ok_or_else(overflow)?)
+                .map(Field::Date)
+                .ok_or_else(overflow)
+        }
+        (Field::Date(date), Subtract, Field::Int(days)) => date
+            .checked_sub_signed(TimeDelta::try_days(days).ok_or_else(overflow)?)
+            .map(Field::Date)
+            .ok_or_else(overflow),
+        (Field::Date(left), Subtract, Field::Date(right)) => {
+            Ok(Field::Int((left - right).num_days()))
+        }
+        (
+            left @ (Field::Date(_) | Field::Timestamp(_) | Field::Time(_)),
+            Add | Subtract,
+            Field::Interval(interval),
+        ) => {
+            let interval = match operator {
+                Subtract => Interval {
+                    months: interval.months.checked_neg().ok_or_else(overflow)?,
+                    micros: interval.micros.checked_neg().ok_or_else(overflow)?,
+                },
+                _ => interval,
+            };
+            shift(left, interval).ok_or_else(overflow)
+        }
+        (
+            Field::Interval(interval),
+            Add,
+            right @ (Field::Date(_) | Field::Timestamp(_) | Field::Time(_)),
+        ) => shift(right, interval).ok_or_else(overflow),
+        (
+            left @ (Field::Date(_) | Field::Timestamp(_)),
+            Subtract,
+            right @ (Field::Date(_) | Field::Timestamp(_)),
+        ) => {
+            let micros = (as_timestamp(&left)? - as_timestamp(&right)?)
+                .num_microseconds()
+                .ok_or_else(overflow)?;
+            Ok(Field::Interval(Interval { months: 0, micros }))
+        }
+        (Field::Time(left), Subtract, Field::Time(right)) => {
+            let micros = Field::time_to_micros(left) - Field::time_to_micros(right);
+            Ok(Field::Interval(Interval { months: 0, micros }))
+        }
+        (Field::Interval(left), Add | Subtract, Field::Interval(right)) => {
+            let (months, micros) = match operator {
+                Add => (
+                    left.months.checked_add(right.months),
+                    left.micros.checked_add(right.micros),
+                ),
+                _ => (
+                    left.months.checked_sub(right.months),
+                    left.micros.checked_sub(right.micros),
+                ),
+            };
+            Ok(Field::Interval(Interval {
+                months: months.ok_or_else(overflow)?,
+                micros: micros.ok_or_else(overflow)?,
+            }))
+        }
+        (left, operator, right) => Err(BEErrors::InvalidExpression(format!(
+            "{left:?} {operator:?} {right:?} is not supported"
+        ))),
+    }
+}
+
+/// Dates count as midnight of that day when compared with or subtracted from timestamps.
+pub(crate) fn as_timestamp(field: &Field) -> BEResult<NaiveDateTime> {
+    match field {
+        Field::Date(date) => Ok(date.and_time(NaiveTime::MIN)),
+        Field::Timestamp(timestamp) => Ok(*timestamp),
+        _ => Err(BEErrors::InvalidExpression(format!(
+            "{field:?} is not a timestamp"
+        ))),
+    }
+}
+
+fn shift(field: Field, interval: Interval) -> Option<Field> {
+    let delta = TimeDelta::microseconds(interval.micros);
+    if let Field::Time(time) = field {
+        return Some(Field::Time(time.overflowing_add_signed(delta).0));
+    }
+
+    let timestamp = as_timestamp(&field).ok()?;
+    let timestamp = if interval.months >= 0 {
+        timestamp.checked_add_months(Months::new(interval.months as u32))?
+    } else {
+        timestamp.checked_sub_months(Months::new(interval.months.unsigned_abs()))?
+    };
+    timestamp.checked_add_signed(delta).map(Field::Timestamp)
+}
@@ -6,6 +6,7 @@ use crate::errors::DbError;
 pub enum MetaCommand {
     Exit,
     Stats,
+    Check,
     DbPath(DbPath),
 }
 
@@ -21,6 +22,7 @@ impl FromStr for MetaCommand {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             ".exit" => Ok(MetaCommand::Exit),
+            ".check" => Ok(MetaCommand::Check),
             _ => Err(DbError::UnrecognizedCommand(s.to_owned())),
         }
     }
@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use nom::{
+    branch::alt,
+    bytes::complete::tag_no_case,
+    character::complete::{alphanumeric1, space1},
+    combinator::opt,
+    sequence::tuple,
+};
+
+use crate::{
+    definitions::{column::Column, table_definition::TableName, ColumnName, NomParsable},
+    errors::DbError,
+};
+
+/// Schema change applied by `alter table`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterTableAction {
+    /// `add [column] <name> <type> [default <value>]`
+    AddColumn(Column),
+    /// `drop [column] <name>`
+    DropColumn(ColumnName),
+    /// `rename [column] <old> to <new>`
+    RenameColumn(ColumnName, ColumnName),
+    /// `rename to <new>`
+    RenameTable(TableName),
+}
+
+/// `alter table <name> <action>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlterTableStatement(pub TableName, pub AlterTableAction);
+
+fn parse_add_column(input: &str) -> nom::IResult<&str, AlterTableAction> {
+    let (left, (_, _, _, column)) = tuple((
+        tag_no_case("add"),
+        space1,
+        opt(tuple((tag_no_case("column"), space1))),
+        Column::nom_parse,
+    ))(input)?;
+
+    Ok((left, AlterTableAction::AddColumn(column)))
+}
+
+fn parse_drop_column(input: &str) -> nom::IResult<&str, AlterTableAction> {
+    let (left, (_, _, _, name)) = tuple((
+        tag_no_case("drop"),
+        space1,
+        opt(tuple((tag_no_case("column"), space1))),
+        alphanumeric1,
+    ))(input)?;
+
+    Ok((left, AlterTableAction::DropColumn(ColumnName::from(name))))
+}
+
+fn parse_rename_table(input: &str) -> nom::IResult<&str, AlterTableAction> {
+    let (left, (_, _, _, _, name)) = tuple((
+        tag_no_case("rename"),
+        space1,
+        tag_no_case("to"),
+        space1,
+        alphanumeric1,
+    ))(input)?;
+
+    Ok((
+        left,
+        AlterTableAction::RenameTable(TableName(String::from(name))),
+    ))
+}
+
+fn parse_rename_column(input: &str) -> nom::IResult<&str, AlterTableAction> {
+    let (left, (_, _, _, old_name, _, _, _, new_name)) = tuple((
+        tag_no_case("rename"),
+        space1,
+        opt(tuple((tag_no_case("column"), space1))),
+        alphanumeric1,
+        space1,
+        tag_no_case("to"),
+        space1,
+        alphanumeric1,
+    ))(input)?;
+
+    Ok((
+        left,
+        AlterTableAction::RenameColumn(ColumnName::from(old_name), ColumnName::from(new_name)),
+    ))
+}
+
+impl NomParsable for AlterTableStatement {
+    fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
+        let (left, (_, _, _, _, table_name, _, action)) = tuple((
+            tag_no_case("alter"),
+            space1,
+            tag_no_case("table"),
+            space1,
+            alphanumeric1,
+            space1,
+            alt((
+                parse_add_column,
+                parse_drop_column,
+                parse_rename_table,
+                parse_rename_column,
+            )),
+        ))(input.trim())?;
+
+        Ok((
+            left,
+            AlterTableStatement(TableName(String::from(table_name)), action),
+        ))
+    }
+}
+
+impl FromStr for AlterTableStatement {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(AlterTableStatement::nom_parse(s)
+            .map_err(|err| anyhow!(format!("{err}")))?
+            .1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use crate::definitions::column::ColumnType;
+
+    use super::*;
+
+    #[rstest]
+    #[case(
+        "alter table student add column grade int default 1;",
+        AlterTableAction::AddColumn(Column(
+            String::from("grade"),
+            ColumnType::Int,
+            Some(String::from("1"))
+        ))
+    )]
+    #[case(
+        "alter table student add city text default 'new york';",
+        AlterTableAction::AddColumn(Column(
+            String::from("city"),
+            ColumnType::Text,
+            Some(String::from("'new york'"))
+        ))
+    )]
+    #[case(
+        "alter table student add grade int;",
+        AlterTableAction::AddColumn(Column::new("grade", ColumnType::Int))
+    )]
+    #[case(
+        "alter table student drop column age;",
+        AlterTableAction::DropColumn(ColumnName::from("age"))
+    )]
+    #[case(
+        "alter table student rename column age to years;",
+        AlterTableAction::RenameColumn(ColumnName::from("age"), ColumnName::from("years"))
+    )]
+    #[case(
+        "alter table student rename age to years;",
+        AlterTableAction::RenameColumn(ColumnName::from("age"), ColumnName::from("years"))
+    )]
+    #[case(
+        "ALTER TABLE student RENAME TO pupil;",
+        AlterTableAction::RenameTable(TableName(String::from("pupil")))
+    )]
+    fn test_parse_alter(
+        #[case] statement: &str,
+        #[case] action: AlterTableAction,
+    ) -> Result<(), DbError> {
+        assert_eq!(
+            AlterTableStatement(TableName(String::from("student")), action),
+            AlterTableStatement::from_str(statement)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::{alphanumeric1, space0, space1},
+    combinator::opt,
+    sequence::tuple,
+};
+
+use crate::{
+    definitions::{
+        table_definition::{parse_columns, TableDefinition, TableName},
+        NomParsable,
+    },
+    errors::DbError,
+};
+
+use super::select::{parse_select_statement, SelectStatement};
+
+// parses "create table [if not exists] <name>" into the name and the if not exists flag
+fn parse_create_table(input: &str) -> nom::IResult<&str, (TableName, bool)> {
+    let (left, (_, _, _, _, if_not_exists, table_name)) = tuple((
+        tag_no_case("create"),
+        space1,
+        tag_no_case("table"),
+        space1,
+        opt(tuple((
+            tag_no_case("if"),
+            space1,
+            tag_no_case("not"),
+            space1,
+            tag_no_case("exists"),
+            space1,
+        ))),
+        alphanumeric1,
+    ))(input.trim())?;
+
+    Ok((
+        left,
+        (TableName(String::from(table_name)), if_not_exists.is_some()),
+    ))
+}
+
+/// Whether a create takes its columns from a select rather than a column list, told
+/// apart by `as` following the table name.
+pub(crate) fn selects_columns(input: &str) -> bool {
+    tuple((parse_create_table, space1, tag_no_case("as"), space1))(input).is_ok()
+}
+
+/// `create table [if not exists] <name> (<columns>)`, the flag tells whether an existing
+/// table is tolerated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateTableStatement(pub TableDefinition, pub bool);
+
+impl NomParsable for CreateTableStatement {
+    fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
+        let (left, ((name, if_not_exists), _, columns)) =
+            tuple((parse_create_table, space0, parse_columns))(input)?;
+
+        Ok((
+            left,
+            CreateTableStatement(
+                TableDefinition {
+                    name,
+                    columns,
+                    schema_history: Vec::new(),
+                },
+                if_not_exists,
+            ),
+        ))
+    }
+}
+
+impl FromStr for CreateTableStatement {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(CreateTableStatement::nom_parse(s)
+            .map_err(|err| anyhow!(format!("{err}")))?
+            .1)
+    }
+}
+
+/// `create table [if not exists] <name> as select ...` creates a table with the selected
+/// columns and fills it with the selected rows.
+#[derive(Debug, Clone)]
+pub struct CreateTableAsStatement(pub TableName, pub bool, pub SelectStatement);
+
+impl NomParsable for CreateTableAsStatement {
+    fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
+        let (left, ((name, if_not_exists), _, _, _, select)) = tuple((
+            parse_create_table,
+            space1,
+            tag_no_case("as"),
+            space1,
+            parse_select_statement,
+        ))(input)?;
+
+        Ok((left, CreateTableAsStatement(name, if_not_exists, select)))
+    }
+}
+
+impl FromStr for CreateTableAsStatement {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(CreateTableAsStatement::nom_parse(s)
+            .map_err(|err| anyhow!(format!("{err}")))?
+            .1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::definitions::column::{Column, ColumnType};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_create_if_not_exists() -> Result<(), DbError> {
+        let statement =
+            CreateTableStatement::from_str("create table if not exists test (id int, name text);")?;
+
+        assert!(statement.1);
+        assert_eq!(TableName(String::from("test")), statement.0.name);
+        assert_eq!(
+            vec![
+                Column::new("id", ColumnType::Int),
+                Column::new("name", ColumnType::Text)
+            ],
+            statement.0.columns
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_create_as_select() -> Result<(), DbError> {
+        let statement = CreateTableAsStatement::from_str(
+            "create table alumni as select id, name from student where age > 21;",
+        )?;
+
+        assert_eq!(TableName(String::from("alumni")), statement.0);
+        assert!(!statement.1);
+        assert_eq!(TableName(String::from("student")), statement.2 .0);
+        assert!(CreateTableStatement::from_str(
+            "create table alumni as select id, name from student;"
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_decimal_columns() -> Result<(), DbError> {
+        let statement = CreateTableStatement::from_str(
+            "create table price (amount decimal(10, 2), rate numeric(5), total decimal);",
+        )?;
+
+        assert_eq!(
+            vec![
+                Column::new("amount", ColumnType::Decimal(10, 2)),
+                Column::new("rate", ColumnType::Decimal(5, 0)),
+                Column::new("total", ColumnType::Decimal(18, 0))
+            ],
+            statement.0.columns
+        );
+        assert_eq!("decimal(10,2)", ColumnType::Decimal(10, 2).to_string());
+        assert!(
+            CreateTableStatement::from_str("create table price (amount decimal(39, 2));").is_err()
+        );
+        assert!(
+            CreateTableStatement::from_str("create table price (amount decimal(4, 5));").is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_character_columns() -> Result<(), DbError> {
+        let statement = CreateTableStatement::from_str(
+            "create table person (name varchar(20), code char(2), initial character, kind CHAR (3));",
+        )?;
+
+        assert_eq!(
+            vec![
+                Column::new("name", ColumnType::Varchar(20)),
+                Column::new("code", ColumnType::Char(2)),
+                Column::new("initial", ColumnType::Char(1)),
+                Column::new("kind", ColumnType::Char(3))
+            ],
+            statement.0.columns
+        );
+        assert_eq!("varchar(20)", ColumnType::Varchar(20).to_string());
+        assert!(CreateTableStatement::from_str("create table person (name varchar);").is_err());
+        assert!(CreateTableStatement::from_str("create table person (name varchar(0));").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::{alphanumeric1, space1},
+    combinator::opt,
+    sequence::tuple,
+};
+
+use crate::{
+    definitions::{table_definition::TableName, NomParsable},
+    errors::DbError,
+};
+
+/// `drop table [if exists] <name>`, the flag tells whether a missing table is tolerated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropTableStatement(pub TableName, pub bool);
+
+impl NomParsable for DropTableStatement {
+    fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
+        let (left, (_, _, _, _, if_exists, table_name)) = tuple((
+            tag_no_case("drop"),
+            space1,
+            tag_no_case("table"),
+            space1,
+            opt(tuple((
+                tag_no_case("if"),
+                space1,
+                tag_no_case("exists"),
+                space1,
+            ))),
+            alphanumeric1,
+        ))(input.trim())?;
+
+        Ok((
+            left,
+            DropTableStatement(TableName(String::from(table_name)), if_exists.is_some()),
+        ))
+    }
+}
+
+impl FromStr for DropTableStatement {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(DropTableStatement::nom_parse(s)
+            .map_err(|err| anyhow!(format!("{err}")))?
+            .1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("drop table student;", false)]
+    #[case("DROP TABLE IF EXISTS student;", true)]
+    fn test_parse_drop(#[case] statement: &str, #[case] if_exists: bool) -> Result<(), DbError> {
+        assert_eq!(
+            DropTableStatement(TableName(String::from("student")), if_exists),
+            DropTableStatement::from_str(statement)?
+        );
+        Ok(())
+    }
+}
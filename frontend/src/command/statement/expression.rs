@@ -0,0 +1,509 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{
+        alphanumeric1, char, digit0, digit1, hex_digit0, one_of, space0, space1,
+    },
+    combinator::{map, opt, recognize, value, verify},
+    multi::{many0, many1, separated_list0},
+    sequence::{delimited, preceded, tuple},
+    IResult,
+};
+
+use crate::definitions::ColumnName;
+
+use super::insert::{parse_placeholder, Parameter, Placeholder};
+
+const KEYWORDS: [&str; 8] = [
+    "and", "or", "not", "from", "where", "select", "true", "false",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComparisonOperator {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ArithmeticOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+/// Expression used by `where` clauses.
+/// Literals are kept as text and get their type from the column they are compared with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Column(ColumnName),
+    Literal(String),
+    /// Hex literal like `x'cafe'`, kept as the bytes it spells.
+    Blob(Vec<u8>),
+    Placeholder(Placeholder),
+    /// Parameter a prepared statement bound in place of a placeholder, keeping its type.
+    Bound(Parameter),
+    /// `interval '1 day 2 hours'`, keeping the quoted text.
+    Interval(String),
+    /// Call of a built in function like `now()` or `date_part('year', born)`.
+    Function(String, Vec<Expression>),
+    Comparison(Box<Expression>, ComparisonOperator, Box<Expression>),
+    Arithmetic(Box<Expression>, ArithmeticOperator, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+}
+
+impl Expression {
+    /// All columns referenced anywhere in the expression.
+    pub fn columns(&self) -> Vec<&ColumnName> {
+        match self {
+            Expression::Column(column) => vec![column],
+            Expression::Literal(_)
+            | Expression::Blob(_)
+            | Expression::Bound(_)
+            | Expression::Placeholder(_)
+            | Expression::Interval(_) => Vec::new(),
+            Expression::Function(_, arguments) => {
+                arguments.iter().flat_map(Expression::columns).collect()
+            }
+            Expression::Comparison(left, _, right)
+            | Expression::Arithmetic(left, _, right)
+            | Expression::And(left, right)
+            | Expression::Or(left, right) => {
+                let mut columns = left.columns();
+                columns.append(&mut right.columns());
+                columns
+            }
+            Expression::Not(inner) => inner.columns(),
+        }
+    }
+
+    /// Names of all functions called anywhere in the expression.
+    pub fn functions(&self) -> Vec<&str> {
+        match self {
+            Expression::Function(name, arguments) => {
+                let mut functions = vec![name.as_str()];
+                functions.extend(arguments.iter().flat_map(Expression::functions));
+                functions
+            }
+            Expression::Column(_)
+            | Expression::Literal(_)
+            | Expression::Blob(_)
+            | Expression::Bound(_)
+            | Expression::Placeholder(_)
+            | Expression::Interval(_) => Vec::new(),
+            Expression::Comparison(left, _, right)
+            | Expression::Arithmetic(left, _, right)
+            | Expression::And(left, right)
+            | Expression::Or(left, right) => {
+                let mut functions = left.functions();
+                functions.append(&mut right.functions());
+                functions
+            }
+            Expression::Not(inner) => inner.functions(),
+        }
+    }
+
+    /// Visits every placeholder in the expression, left to right.
+    pub fn placeholders_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Expression::Placeholder(_) => vec![self],
+            Expression::Column(_)
+            | Expression::Literal(_)
+            | Expression::Blob(_)
+            | Expression::Bound(_)
+            | Expression::Interval(_) => Vec::new(),
+            Expression::Function(_, arguments) => arguments
+                .iter_mut()
+                .flat_map(Expression::placeholders_mut)
+                .collect(),
+            Expression::Comparison(left, _, right)
+            | Expression::Arithmetic(left, _, right)
+            | Expression::And(left, right)
+            | Expression::Or(left, right) => {
+                let mut placeholders = left.placeholders_mut();
+                placeholders.append(&mut right.placeholders_mut());
+                placeholders
+            }
+            Expression::Not(inner) => inner.placeholders_mut(),
+        }
+    }
+}
+
+// parses 'it''s' into "it's"
+pub(crate) fn parse_quoted_string(input: &str) -> IResult<&str, String> {
+    let (mut left, _) = tag("'")(input)?;
+    let mut result = String::new();
+    loop {
+        let Some(end) = left.find('\'') else {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                left,
+                nom::error::ErrorKind::Char,
+            )));
+        };
+        result.push_str(&left[..end]);
+        left = &left[end + 1..];
+        match left.strip_prefix('\'') {
+            Some(rest) => {
+                result.push('\'');
+                left = rest;
+            }
+            None => return Ok((left, result)),
+        }
+    }
+}
+
+// parses hex blob literals like x'CAFE' into the bytes [0xca, 0xfe]
+pub(crate) fn parse_hex_literal(input: &str) -> IResult<&str, Vec<u8>> {
+    map(
+        delimited(
+            tag_no_case("x'"),
+            verify(hex_digit0, |it: &str| it.len().is_multiple_of(2)),
+            char('\''),
+        ),
+        |it: &str| {
+            (0..it.len())
+                .step_by(2)
+                .map(|index| u8::from_str_radix(&it[index..index + 2], 16).unwrap())
+                .collect()
+        },
+    )(input)
+}
+
+// parses integers like "-12" and reals like "1.5", ".5", "2." and "1e-3"
+pub(crate) fn parse_number(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((
+        opt(char('-')),
+        alt((
+            recognize(tuple((digit1, opt(tuple((char('.'), digit0)))))),
+            recognize(tuple((char('.'), digit1))),
+        )),
+        opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+    )))(input)
+}
+
+fn parse_bool(input: &str) -> IResult<&str, &str> {
+    verify(alphanumeric1, |it: &str| {
+        it.eq_ignore_ascii_case("true") || it.eq_ignore_ascii_case("false")
+    })(input)
+}
+
+pub(crate) fn parse_identifier(input: &str) -> IResult<&str, &str> {
+    verify(alphanumeric1, |it: &str| {
+        !KEYWORDS
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(it))
+    })(input)
+}
+
+fn parse_interval(input: &str) -> IResult<&str, Expression> {
+    map(
+        preceded(
+            tuple((tag_no_case("interval"), space1)),
+            parse_quoted_string,
+        ),
+        Expression::Interval,
+    )(input)
+}
+
+fn parse_function(input: &str) -> IResult<&str, Expression> {
+    let (left, (name, _, arguments)) = tuple((
+        recognize(many1(alt((alphanumeric1, tag("_"))))),
+        space0,
+        delimited(
+            tuple((char('('), space0)),
+            separated_list0(tuple((space0, char(','), space0)), parse_expression),
+            tuple((space0, char(')'))),
+        ),
+    ))(input)?;
+    Ok((left, Expression::Function(name.to_lowercase(), arguments)))
+}
+
+fn parse_operand(input: &str) -> IResult<&str, Expression> {
+    alt((
+        parse_interval,
+        parse_function,
+        delimited(
+            tuple((char('('), space0)),
+            parse_expression,
+            tuple((space0, char(')'))),
+        ),
+        map(parse_placeholder, Expression::Placeholder),
+        map(parse_hex_literal, Expression::Blob),
+        map(parse_quoted_string, Expression::Literal),
+        map(parse_number, |it| Expression::Literal(String::from(it))),
+        // negation is subtraction from zero, so it needs no operator of its own
+        map(preceded(tuple((char('-'), space0)), parse_operand), |it| {
+            Expression::Arithmetic(
+                Box::new(Expression::Literal(String::from("0"))),
+                ArithmeticOperator::Subtract,
+                Box::new(it),
+            )
+        }),
+        map(parse_bool, |it| Expression::Literal(it.to_lowercase())),
+        map(parse_identifier, |it| {
+            Expression::Column(ColumnName(String::from(it)))
+        }),
+    ))(input)
+}
+
+fn parse_comparison_operator(input: &str) -> IResult<&str, ComparisonOperator> {
+    use ComparisonOperator::*;
+    alt((
+        value(LtEq, tag("<=")),
+        value(GtEq, tag(">=")),
+        value(NotEq, tag("!=")),
+        value(NotEq, tag("<>")),
+        value(Eq, tag("=")),
+        value(Lt, tag("<")),
+        value(Gt, tag(">")),
+    ))(input)
+}
+
+// folds "a op b op c" left to right, with `operator` parsing the operators of one precedence level
+fn parse_arithmetic<'a>(
+    input: &'a str,
+    operand: fn(&'a str) -> IResult<&'a str, Expression>,
+    operator: fn(&'a str) -> IResult<&'a str, ArithmeticOperator>,
+) -> IResult<&'a str, Expression> {
+    let (left, first) = operand(input)?;
+    let (left, rest) = many0(tuple((delimited(space0, operator, space0), operand)))(left)?;
+    Ok((
+        left,
+        rest.into_iter().fold(first, |acc, (operator, it)| {
+            Expression::Arithmetic(Box::new(acc), operator, Box::new(it))
+        }),
+    ))
+}
+
+fn parse_term(input: &str) -> IResult<&str, Expression> {
+    parse_arithmetic(input, parse_operand, |input| {
+        alt((
+            value(ArithmeticOperator::Multiply, char('*')),
+            value(ArithmeticOperator::Divide, char('/')),
+        ))(input)
+    })
+}
+
+fn parse_sum(input: &str) -> IResult<&str, Expression> {
+    parse_arithmetic(input, parse_term, |input| {
+        alt((
+            value(ArithmeticOperator::Add, char('+')),
+            value(ArithmeticOperator::Subtract, char('-')),
+        ))(input)
+    })
+}
+
+fn parse_comparison(input: &str) -> IResult<&str, Expression> {
+    let (left, operand) = parse_sum(input)?;
+    let (left, comparison) = opt(tuple((
+        space0,
+        parse_comparison_operator,
+        space0,
+        parse_sum,
+    )))(left)?;
+    let expression = match comparison {
+        Some((_, operator, _, right)) => {
+            Expression::Comparison(Box::new(operand), operator, Box::new(right))
+        }
+        None => operand,
+    };
+    Ok((left, expression))
+}
+
+fn parse_not(input: &str) -> IResult<&str, Expression> {
+    alt((
+        map(
+            preceded(tuple((tag_no_case("not"), space1)), parse_not),
+            |it| Expression::Not(Box::new(it)),
+        ),
+        parse_comparison,
+    ))(input)
+}
+
+fn parse_and(input: &str) -> IResult<&str, Expression> {
+    let (left, first) = parse_not(input)?;
+    let (left, rest) = many0(preceded(
+        tuple((space1, tag_no_case("and"), space1)),
+        parse_not,
+    ))(left)?;
+    Ok((
+        left,
+        rest.into_iter().fold(first, |acc, it| {
+            Expression::And(Box::new(acc), Box::new(it))
+        }),
+    ))
+}
+
+/// Parses an expression where `or` binds weaker than `and`, which binds weaker than `not`.
+/// Comparisons bind weaker than `+` and `-`, which bind weaker than `*` and `/`.
+pub fn parse_expression(input: &str) -> IResult<&str, Expression> {
+    let (left, first) = parse_and(input)?;
+    let (left, rest) = many0(preceded(
+        tuple((space1, tag_no_case("or"), space1)),
+        parse_and,
+    ))(left)?;
+    Ok((
+        left,
+        rest.into_iter()
+            .fold(first, |acc, it| Expression::Or(Box::new(acc), Box::new(it))),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn column(name: &str) -> Box<Expression> {
+        Box::new(Expression::Column(ColumnName::from(name)))
+    }
+
+    fn literal(value: &str) -> Box<Expression> {
+        Box::new(Expression::Literal(String::from(value)))
+    }
+
+    #[test]
+    fn test_precedence() {
+        let (left, expression) =
+            parse_expression("age >= 21 and not name = 'o''neil' or id<-3;").unwrap();
+
+        assert_eq!(";", left);
+        assert_eq!(
+            Expression::Or(
+                Box::new(Expression::And(
+                    Box::new(Expression::Comparison(
+                        column("age"),
+                        ComparisonOperator::GtEq,
+                        literal("21")
+                    )),
+                    Box::new(Expression::Not(Box::new(Expression::Comparison(
+                        column("name"),
+                        ComparisonOperator::Eq,
+                        literal("o'neil")
+                    ))))
+                )),
+                Box::new(Expression::Comparison(
+                    column("id"),
+                    ComparisonOperator::Lt,
+                    literal("-3")
+                ))
+            ),
+            expression
+        );
+    }
+
+    #[rstest]
+    #[case("(id = 1 or id = 2) and age > $1", 1)]
+    #[case("id = ? or id = ?", 2)]
+    fn test_parenthesis_and_placeholders(#[case] input: &str, #[case] placeholders: usize) {
+        let (left, mut expression) = parse_expression(input).unwrap();
+        assert_eq!("", left);
+        assert_eq!(placeholders, expression.placeholders_mut().len());
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        let (left, expression) = parse_expression("price * 2 - 1.5e2 > -.5").unwrap();
+
+        assert_eq!("", left);
+        assert_eq!(
+            Expression::Comparison(
+                Box::new(Expression::Arithmetic(
+                    Box::new(Expression::Arithmetic(
+                        column("price"),
+                        ArithmeticOperator::Multiply,
+                        literal("2")
+                    )),
+                    ArithmeticOperator::Subtract,
+                    literal("1.5e2")
+                )),
+                ComparisonOperator::Gt,
+                literal("-.5")
+            ),
+            expression
+        );
+    }
+
+    #[rstest]
+    #[case("12", "12")]
+    #[case("-1.25;", "-1.25")]
+    #[case("2.", "2.")]
+    #[case("6.02E+23)", "6.02E+23")]
+    #[case("1e", "1")]
+    fn test_parse_number(#[case] input: &str, #[case] number: &str) {
+        assert_eq!(number, parse_number(input).unwrap().1);
+    }
+
+    #[test]
+    fn test_bool_literals_and_columns() {
+        let (left, expression) = parse_expression("active and deleted = FALSE").unwrap();
+
+        assert_eq!("", left);
+        assert_eq!(
+            Expression::And(
+                column("active"),
+                Box::new(Expression::Comparison(
+                    column("deleted"),
+                    ComparisonOperator::Eq,
+                    literal("false")
+                ))
+            ),
+            expression
+        );
+    }
+
+    #[test]
+    fn test_functions_and_intervals() {
+        let (left, expression) =
+            parse_expression("date_part('year', born) < now() - interval '1 day'").unwrap();
+
+        assert_eq!("", left);
+        assert_eq!(
+            Expression::Comparison(
+                Box::new(Expression::Function(
+                    String::from("date_part"),
+                    vec![*literal("year"), *column("born")]
+                )),
+                ComparisonOperator::Lt,
+                Box::new(Expression::Arithmetic(
+                    Box::new(Expression::Function(String::from("now"), Vec::new())),
+                    ArithmeticOperator::Subtract,
+                    Box::new(Expression::Interval(String::from("1 day")))
+                ))
+            ),
+            expression
+        );
+        assert_eq!(vec![&ColumnName::from("born")], expression.columns());
+    }
+
+    #[test]
+    fn test_hex_literals() {
+        let (left, expression) = parse_expression("payload = X'CAFE' or x = x''").unwrap();
+
+        assert_eq!("", left);
+        assert_eq!(
+            Expression::Or(
+                Box::new(Expression::Comparison(
+                    column("payload"),
+                    ComparisonOperator::Eq,
+                    Box::new(Expression::Blob(vec![0xca, 0xfe]))
+                )),
+                Box::new(Expression::Comparison(
+                    column("x"),
+                    ComparisonOperator::Eq,
+                    Box::new(Expression::Blob(Vec::new()))
+                ))
+            ),
+            expression
+        );
+        assert!(parse_hex_literal("x'abc'").is_err());
+        assert!(parse_hex_literal("x'zz'").is_err());
+    }
+}
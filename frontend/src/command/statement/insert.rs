@@ -1,23 +1,138 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 use anyhow::anyhow;
 use nom::{
+    branch::alt,
     bytes::complete::{tag, tag_no_case},
-    character::complete::{alphanumeric1, space0, space1},
-    combinator::opt,
+    character::complete::{alphanumeric1, digit1, space0, space1},
+    combinator::{cut, eof, map, map_res, opt, value},
     multi::separated_list1,
-    sequence::{delimited, tuple},
+    sequence::{delimited, preceded, terminated, tuple},
 };
 
 use crate::{
-    definitions::{table_definition::TableName, NomParsable},
+    definitions::{table_definition::TableName, ColumnName, NomParsable},
     errors::DbError,
 };
 
+use super::{
+    expression::{parse_hex_literal, parse_number, parse_quoted_string},
+    select::{parse_select_statement, SelectStatement},
+};
+
+/// Parameter marker in a statement that gets its value bound at execution time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Placeholder {
+    /// `?`, numbered by its position among the other placeholders of the statement.
+    Next,
+    /// `$n`, explicitly numbered starting from 1.
+    Numbered(usize),
+}
+
+impl Display for Placeholder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Placeholder::Next => write!(f, "?"),
+            Placeholder::Numbered(number) => write!(f, "${number}"),
+        }
+    }
+}
+
+/// Typed value bound to a statement placeholder.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parameter {
+    Int(i64),
+    Text(String),
+    Real(f64),
+    Bool(bool),
+    Blob(Vec<u8>),
+}
+
+impl Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Parameter::Int(value) => write!(f, "{value}"),
+            Parameter::Text(value) => write!(f, "{value}"),
+            Parameter::Real(value) => write!(f, "{value:?}"),
+            Parameter::Bool(value) => write!(f, "{value}"),
+            Parameter::Blob(value) => write!(f, "{}", hex(value)),
+        }
+    }
+}
+
+impl From<i64> for Parameter {
+    fn from(value: i64) -> Self {
+        Parameter::Int(value)
+    }
+}
+
+impl From<i32> for Parameter {
+    fn from(value: i32) -> Self {
+        Parameter::Int(value.into())
+    }
+}
+
+impl From<f64> for Parameter {
+    fn from(value: f64) -> Self {
+        Parameter::Real(value)
+    }
+}
+
+impl From<bool> for Parameter {
+    fn from(value: bool) -> Self {
+        Parameter::Bool(value)
+    }
+}
+
+impl From<Vec<u8>> for Parameter {
+    fn from(value: Vec<u8>) -> Self {
+        Parameter::Blob(value)
+    }
+}
+
+impl From<&[u8]> for Parameter {
+    fn from(value: &[u8]) -> Self {
+        Parameter::Blob(value.to_vec())
+    }
+}
+
+impl From<&str> for Parameter {
+    fn from(value: &str) -> Self {
+        Parameter::Text(String::from(value))
+    }
+}
+
+impl From<String> for Parameter {
+    fn from(value: String) -> Self {
+        Parameter::Text(value)
+    }
+}
+
+// renders bytes like \xcafe
+fn hex(bytes: &[u8]) -> String {
+    let digits = bytes.iter().map(|it| format!("{it:02x}"));
+    format!("\\x{}", digits.collect::<String>())
+}
+
+pub(crate) fn parse_placeholder(input: &str) -> nom::IResult<&str, Placeholder> {
+    alt((
+        value(Placeholder::Next, tag("?")),
+        map(
+            map_res(preceded(tag("$"), digit1), str::parse::<usize>),
+            Placeholder::Numbered,
+        ),
+    ))(input)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Value {
     NamedValue(String, String),
     UnnamedValue(String),
+    /// Hex literal like `x'cafe'` given for the named column, kept as its bytes.
+    NamedBlob(String, Vec<u8>),
+    UnnamedBlob(Vec<u8>),
+    NamedPlaceholder(String, Placeholder),
+    UnnamedPlaceholder(Placeholder),
 }
 
 impl Value {
@@ -31,10 +146,42 @@ impl Value {
         Value::UnnamedValue(value.into())
     }
 
+    /// Text form of the value, with blobs rendered like `\xcafe`.
     pub fn value(self) -> String {
         match self {
             Value::NamedValue(_, value) => value,
             Value::UnnamedValue(value) => value,
+            Value::NamedBlob(_, bytes) | Value::UnnamedBlob(bytes) => hex(&bytes),
+            Value::NamedPlaceholder(_, placeholder) => placeholder.to_string(),
+            Value::UnnamedPlaceholder(placeholder) => placeholder.to_string(),
+        }
+    }
+}
+
+/// Single entry of a `values (..)` list, either a literal or a parameter marker.
+#[derive(Debug, Clone)]
+enum ValueToken {
+    Literal(String),
+    Blob(Vec<u8>),
+    Placeholder(Placeholder),
+}
+
+impl ValueToken {
+    fn unnamed(self) -> Value {
+        match self {
+            ValueToken::Literal(value) => Value::UnnamedValue(value),
+            ValueToken::Blob(bytes) => Value::UnnamedBlob(bytes),
+            ValueToken::Placeholder(placeholder) => Value::UnnamedPlaceholder(placeholder),
+        }
+    }
+
+    fn named(self, name: &str) -> Value {
+        match self {
+            ValueToken::Literal(value) => Value::NamedValue(String::from(name), value),
+            ValueToken::Blob(bytes) => Value::NamedBlob(String::from(name), bytes),
+            ValueToken::Placeholder(placeholder) => {
+                Value::NamedPlaceholder(String::from(name), placeholder)
+            }
         }
     }
 }
@@ -50,37 +197,94 @@ fn wrapped_parser(input: &str) -> nom::IResult<&str, Vec<&str>> {
     .map(|res| (res.0, res.1.into_iter().map(|it| it.1).collect()))
 }
 
-fn parse_value_list(input: &str) -> nom::IResult<&str, Vec<Value>> {
-    let just_values = opt(tuple((tag_no_case("values"), space0, wrapped_parser)))(input.trim())?;
-    match just_values {
-        (left, Some(values)) => Ok((
+// parses "(a, 'b c', -1, ?, $2)" into literal and placeholder tokens
+fn wrapped_value_parser(input: &str) -> nom::IResult<&str, Vec<ValueToken>> {
+    delimited(
+        tag("("),
+        separated_list1(
+            tag(","),
+            preceded(
+                space0,
+                alt((
+                    map(parse_placeholder, ValueToken::Placeholder),
+                    map(parse_hex_literal, ValueToken::Blob),
+                    map(parse_quoted_string, ValueToken::Literal),
+                    map(parse_number, |it| ValueToken::Literal(String::from(it))),
+                    map(alphanumeric1, |it| ValueToken::Literal(String::from(it))),
+                )),
+            ),
+        ),
+        tag(")"),
+    )(input.trim())
+}
+
+// parses "(a, b), (c, d)" into one token list per row
+fn wrapped_value_rows(input: &str) -> nom::IResult<&str, Vec<Vec<ValueToken>>> {
+    separated_list1(tuple((space0, tag(","), space0)), wrapped_value_parser)(input.trim())
+}
+
+// once `values` is seen the rows have to follow, so their syntax errors are reported
+// rather than hidden behind the errors of the other insert forms
+fn parse_value_rows(input: &str) -> nom::IResult<&str, Vec<Vec<ValueToken>>> {
+    preceded(
+        tuple((tag_no_case("values"), space0)),
+        cut(terminated(
+            wrapped_value_rows,
+            tuple((space0, opt(tag(";")), space0, eof)),
+        )),
+    )(input.trim())
+}
+
+fn parse_value_list(input: &str) -> nom::IResult<&str, Vec<Vec<Value>>> {
+    if tag_no_case::<_, _, nom::error::Error<&str>>("values")(input.trim()).is_ok() {
+        let (left, rows) = parse_value_rows(input)?;
+        return Ok((
             left,
-            values
-                .2
-                .iter()
-                .map(|v| Value::UnnamedValue(String::from(*v)))
+            rows.into_iter()
+                .map(|row| row.into_iter().map(ValueToken::unnamed).collect())
                 .collect::<Vec<_>>(),
-        )),
-        (left, None) => {
-            let (left, (names, _, _, _, values)) = tuple((
-                wrapped_parser,
-                space1,
-                tag_no_case("values"),
-                space0,
-                wrapped_parser,
-            ))(left.trim())?;
-            let values = names
-                .into_iter()
-                .zip(values.into_iter())
-                .map(|(name, value)| Value::NamedValue(String::from(name), String::from(value)))
-                .collect::<Vec<_>>();
-            Ok((left, values))
-        }
+        ));
     }
+
+    let (left, (names, _, rows)) = tuple((wrapped_parser, space1, parse_value_rows))(input.trim())?;
+    if rows.iter().any(|row| row.len() != names.len()) {
+        return Err(nom::Err::Failure(nom::error::Error::new(
+            left,
+            nom::error::ErrorKind::Verify,
+        )));
+    }
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            names
+                .iter()
+                .zip(row)
+                .map(|(name, value)| value.named(name))
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    Ok((left, rows))
+}
+
+/// Whether an insert takes its rows from a select rather than a values list, told
+/// apart by the keyword following the table name and the optional column list.
+pub(crate) fn selects_rows(input: &str) -> bool {
+    tuple((
+        tag_no_case::<_, _, nom::error::Error<&str>>("insert"),
+        space1,
+        tag_no_case("into"),
+        space1,
+        alphanumeric1,
+        space1,
+        opt(terminated(wrapped_parser, space0)),
+        tag_no_case("select"),
+    ))(input.trim())
+    .is_ok()
 }
 
+/// Insert of one or more rows, given as value lists of equal shape.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct InsertStatement(pub TableName, pub Vec<Value>);
+pub struct InsertStatement(pub TableName, pub Vec<Vec<Value>>);
 
 impl NomParsable for InsertStatement {
     fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
@@ -111,9 +315,53 @@ impl FromStr for InsertStatement {
     }
 }
 
+/// Insert of every row produced by a select into `columns` of the target table.
+/// Selected columns map to target columns by position, an empty column list targets
+/// all columns in table definition order.
+#[derive(Debug, Clone)]
+pub struct InsertSelectStatement(pub TableName, pub Vec<ColumnName>, pub SelectStatement);
+
+impl NomParsable for InsertSelectStatement {
+    fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
+        let (left, (_, _, _, _, table_name, _, columns, select)) = tuple((
+            tag_no_case("insert"),
+            space1,
+            tag_no_case("into"),
+            space1,
+            alphanumeric1,
+            space1,
+            opt(terminated(wrapped_parser, space1)),
+            parse_select_statement,
+        ))(input.trim())?;
+
+        let columns = columns
+            .unwrap_or_default()
+            .into_iter()
+            .map(ColumnName::from)
+            .collect();
+        Ok((
+            left,
+            InsertSelectStatement(TableName(String::from(table_name)), columns, select),
+        ))
+    }
+}
+
+impl FromStr for InsertSelectStatement {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(InsertSelectStatement::nom_parse(s)
+            .map_err(|err| anyhow!(format!("{err}")))?
+            .1)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::definitions::{table_definition::TableName, NomParsable};
+    use crate::{
+        command::statement::StatementCommand,
+        definitions::{table_definition::TableName, NomParsable},
+    };
 
     use super::*;
 
@@ -127,12 +375,12 @@ mod test {
             insert_command.1,
             InsertStatement(
                 TableName(String::from("test")),
-                vec![
+                vec![vec![
                     Value::new_unnamed_value("one"),
                     Value::new_unnamed_value("two"),
                     Value::new_unnamed_value("1234"),
                     Value::new_unnamed_value("five")
-                ]
+                ]]
             )
         );
         Ok(())
@@ -148,14 +396,135 @@ mod test {
             insert_command.1,
             InsertStatement(
                 TableName(String::from("test")),
-                vec![
+                vec![vec![
                     Value::new_named_value("col1", "one"),
                     Value::new_named_value("col2", "two"),
                     Value::new_named_value("col4", "1234"),
                     Value::new_named_value("col3", "five")
+                ]]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_test_with_placeholders() -> Result<(), String> {
+        let command = "insert into test (col1, col2, col3) values (?, $3, one);";
+        let insert_command = InsertStatement::nom_parse(command)
+            .map_err(|err| format!("Failed with error {:?}", err))?;
+
+        assert_eq!(
+            insert_command.1,
+            InsertStatement(
+                TableName(String::from("test")),
+                vec![vec![
+                    Value::NamedPlaceholder(String::from("col1"), Placeholder::Next),
+                    Value::NamedPlaceholder(String::from("col2"), Placeholder::Numbered(3)),
+                    Value::new_named_value("col3", "one"),
+                ]]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_test_with_multiple_rows() -> Result<(), String> {
+        let command = "insert into test (col1, col2) values (one, 1), (two, 2) ,(three,3);";
+        let insert_command = InsertStatement::nom_parse(command)
+            .map_err(|err| format!("Failed with error {:?}", err))?;
+
+        assert_eq!(
+            insert_command.1,
+            InsertStatement(
+                TableName(String::from("test")),
+                vec![
+                    vec![
+                        Value::new_named_value("col1", "one"),
+                        Value::new_named_value("col2", "1")
+                    ],
+                    vec![
+                        Value::new_named_value("col1", "two"),
+                        Value::new_named_value("col2", "2")
+                    ],
+                    vec![
+                        Value::new_named_value("col1", "three"),
+                        Value::new_named_value("col2", "3")
+                    ],
                 ]
             )
         );
         Ok(())
     }
+
+    #[test]
+    fn parse_test_with_short_row() {
+        let command = "insert into test (col1, col2) values (one, 1), (two);";
+        assert!(InsertStatement::from_str(command).is_err());
+    }
+
+    #[test]
+    fn parse_test_with_quoted_values() -> Result<(), String> {
+        let command = "insert into test values ('harry potter', -12, 'it''s');";
+        let insert_command = InsertStatement::nom_parse(command)
+            .map_err(|err| format!("Failed with error {:?}", err))?;
+
+        assert_eq!(
+            insert_command.1 .1,
+            vec![vec![
+                Value::new_unnamed_value("harry potter"),
+                Value::new_unnamed_value("-12"),
+                Value::new_unnamed_value("it's"),
+            ]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_test_with_hex_values() -> Result<(), String> {
+        let command = "insert into file (name, data) values ('\\xcafe', X'CAFE');";
+        let insert_command = InsertStatement::nom_parse(command)
+            .map_err(|err| format!("Failed with error {:?}", err))?;
+
+        assert_eq!(
+            insert_command.1 .1,
+            vec![vec![
+                Value::new_named_value("name", "\\xcafe"),
+                Value::NamedBlob(String::from("data"), vec![0xca, 0xfe]),
+            ]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_test_insert_select() -> Result<(), String> {
+        let command = "insert into alumni (id, name) select id, name from student where age > 21;";
+        let insert_command = InsertSelectStatement::from_str(command)
+            .map_err(|err| format!("Failed with error {:?}", err))?;
+
+        assert_eq!(insert_command.0, TableName(String::from("alumni")));
+        assert_eq!(insert_command.1, vec!["id".into(), "name".into()]);
+        assert_eq!(insert_command.2 .0, TableName(String::from("student")));
+        assert!(insert_command.2 .2.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_values_are_reported() {
+        for (statement, mistake) in [
+            ("insert into student values (1, john;", r#"input: ";""#),
+            (
+                "insert into student values (1, john), (2 jane);",
+                r#"input: ", (2 jane);""#,
+            ),
+            (
+                "insert into student (id, name) values (1, 'a b);",
+                r#"input: ", 'a b);""#,
+            ),
+        ] {
+            let Err(err) = StatementCommand::from_str(statement) else {
+                panic!("{statement} should not parse");
+            };
+            assert!(err.to_string().contains(mistake), "{statement}: {err}");
+        }
+    }
 }
@@ -3,18 +3,38 @@ use std::str::FromStr;
 use nom::{branch::alt, bytes::complete::tag_no_case, IResult};
 use tracing::instrument;
 
-use crate::{definitions::table_definition::TableDefinition, errors::DbError};
+use crate::errors::DbError;
 
-use self::{insert::InsertStatement, select::SelectStatement};
+use self::{
+    alter::AlterTableStatement,
+    create::{CreateTableAsStatement, CreateTableStatement},
+    drop::DropTableStatement,
+    insert::{InsertSelectStatement, InsertStatement},
+    select::SelectStatement,
+    truncate::TruncateTableStatement,
+    vacuum::VacuumStatement,
+};
 
+pub mod alter;
+pub mod create;
+pub mod drop;
+pub mod expression;
 pub mod insert;
 pub mod select;
+pub mod truncate;
+pub mod vacuum;
 
 #[derive(Debug, Clone)]
 pub enum StatementCommand {
     Select(SelectStatement),
     Insert(InsertStatement),
-    Create(TableDefinition),
+    InsertSelect(InsertSelectStatement),
+    Create(CreateTableStatement),
+    CreateAs(CreateTableAsStatement),
+    Drop(DropTableStatement),
+    Truncate(TruncateTableStatement),
+    Alter(AlterTableStatement),
+    Vacuum(VacuumStatement),
 }
 
 impl FromStr for StatementCommand {
@@ -25,8 +45,20 @@ impl FromStr for StatementCommand {
         let command_type = CommandType::from_str(s)?;
         match command_type {
             Select => Ok(StatementCommand::Select(SelectStatement::from_str(s)?)),
+            Insert if insert::selects_rows(s) => Ok(StatementCommand::InsertSelect(
+                InsertSelectStatement::from_str(s)?,
+            )),
             Insert => Ok(StatementCommand::Insert(InsertStatement::from_str(s)?)),
-            Create => Ok(StatementCommand::Create(TableDefinition::from_str(s)?)),
+            Create if create::selects_columns(s) => Ok(StatementCommand::CreateAs(
+                CreateTableAsStatement::from_str(s)?,
+            )),
+            Create => Ok(StatementCommand::Create(CreateTableStatement::from_str(s)?)),
+            Drop => Ok(StatementCommand::Drop(DropTableStatement::from_str(s)?)),
+            Truncate => Ok(StatementCommand::Truncate(
+                TruncateTableStatement::from_str(s)?,
+            )),
+            Alter => Ok(StatementCommand::Alter(AlterTableStatement::from_str(s)?)),
+            Vacuum => Ok(StatementCommand::Vacuum(VacuumStatement::from_str(s)?)),
         }
     }
 }
@@ -35,6 +67,10 @@ enum CommandType {
     Select,
     Insert,
     Create,
+    Drop,
+    Truncate,
+    Alter,
+    Vacuum,
 }
 
 fn parse_select_command(command: &str) -> IResult<&str, CommandType> {
@@ -52,6 +88,26 @@ fn parse_create_command(command: &str) -> IResult<&str, CommandType> {
     Ok(("", CommandType::Create))
 }
 
+fn parse_drop_command(command: &str) -> IResult<&str, CommandType> {
+    let _ = tag_no_case("drop")(command)?;
+    Ok(("", CommandType::Drop))
+}
+
+fn parse_truncate_command(command: &str) -> IResult<&str, CommandType> {
+    let _ = tag_no_case("truncate")(command)?;
+    Ok(("", CommandType::Truncate))
+}
+
+fn parse_alter_command(command: &str) -> IResult<&str, CommandType> {
+    let _ = tag_no_case("alter")(command)?;
+    Ok(("", CommandType::Alter))
+}
+
+fn parse_vacuum_command(command: &str) -> IResult<&str, CommandType> {
+    let _ = tag_no_case("vacuum")(command)?;
+    Ok(("", CommandType::Vacuum))
+}
+
 #[instrument]
 fn parse_command_type(command: &str) -> IResult<&str, CommandType> {
     tracing::info!("");
@@ -59,6 +115,10 @@ fn parse_command_type(command: &str) -> IResult<&str, CommandType> {
         parse_select_command,
         parse_insert_command,
         parse_create_command,
+        parse_drop_command,
+        parse_truncate_command,
+        parse_alter_command,
+        parse_vacuum_command,
     ))(command.trim())?
     .1;
     Ok(("", command))
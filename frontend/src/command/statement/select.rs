@@ -4,8 +4,9 @@ use anyhow::anyhow;
 use nom::{
     bytes::{complete::tag_no_case, streaming::tag},
     character::complete::{alphanumeric1, char},
+    combinator::opt,
     multi::{many0, many1, separated_list0},
-    sequence::{separated_pair, tuple},
+    sequence::{preceded, separated_pair, tuple},
     IResult,
 };
 
@@ -14,22 +15,27 @@ use crate::{
     errors::DbError,
 };
 
+use super::expression::{parse_expression, Expression};
+
+/// Select of `columns` from a table, keeping only rows matching the optional `where` filter.
 #[derive(Debug, Clone)]
-pub struct SelectStatement(pub TableName, pub Vec<ColumnName>);
+pub struct SelectStatement(pub TableName, pub Vec<ColumnName>, pub Option<Expression>);
 
-fn parse_table_name(statement: &str) -> IResult<&str, &str> {
-    let table_name = tuple((
+fn parse_table_name(statement: &str) -> IResult<&str, (&str, Option<Expression>)> {
+    let (_, table_name, filter, _, _) = tuple((
         tag_no_case("from"),
-        many1(char(' ')),
-        alphanumeric1,
+        preceded(many1(char(' ')), alphanumeric1),
+        opt(preceded(
+            tuple((many1(char(' ')), tag_no_case("where"), many1(char(' ')))),
+            parse_expression,
+        )),
         many0(char(' ')),
         char(';'),
     ))(statement)?
-    .1
-     .2;
-    Ok(("", table_name))
+    .1;
+    Ok(("", (table_name, filter)))
 }
-fn parse_select_statement(statement: &str) -> IResult<&str, SelectStatement> {
+pub(crate) fn parse_select_statement(statement: &str) -> IResult<&str, SelectStatement> {
     let left = tag_no_case("select")(statement)?.0.trim();
     let columns_table_name = separated_pair(
         separated_list0(
@@ -46,9 +52,12 @@ fn parse_select_statement(statement: &str) -> IResult<&str, SelectStatement> {
         .into_iter()
         .map(|it| ColumnName(String::from(it)))
         .collect::<Vec<_>>();
-    let table_name = columns_table_name.1 .1.to_owned();
+    let (table_name, filter) = columns_table_name.1 .1;
 
-    Ok(("", SelectStatement(TableName(table_name), columns)))
+    Ok((
+        "",
+        SelectStatement(TableName(table_name.to_owned()), columns, filter),
+    ))
 }
 
 impl FromStr for SelectStatement {
@@ -100,6 +109,24 @@ mod test {
     }
 
     #[test]
+    fn test_select_with_where() -> SError<()> {
+        let statement = "select name from student where age > 20 and name != 'john';";
+        let parse_statement = SelectStatement::from_str(statement)?;
+
+        assert_eq!(parse_statement.0 .0.as_str(), "student");
+        let Some(filter) = parse_statement.2 else {
+            panic!("where clause expected");
+        };
+        assert_eq!(
+            vec![&ColumnName::from("age"), &ColumnName::from("name")],
+            filter.columns()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::match_like_matches_macro, clippy::bool_assert_comparison)]
     fn test_invalid_comma() -> SError<()> {
         let statement = "select name, from student;";
         let Err(err) = SelectStatement::from_str(statement) else {
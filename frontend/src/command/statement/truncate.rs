@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::{alphanumeric1, space1},
+    combinator::opt,
+    sequence::tuple,
+};
+
+use crate::{
+    definitions::{table_definition::TableName, NomParsable},
+    errors::DbError,
+};
+
+/// `truncate [table] <name>` removes every row while keeping the table definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncateTableStatement(pub TableName);
+
+impl NomParsable for TruncateTableStatement {
+    fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
+        let (left, (_, _, _, table_name)) = tuple((
+            tag_no_case("truncate"),
+            space1,
+            opt(tuple((tag_no_case("table"), space1))),
+            alphanumeric1,
+        ))(input.trim())?;
+
+        Ok((
+            left,
+            TruncateTableStatement(TableName(String::from(table_name))),
+        ))
+    }
+}
+
+impl FromStr for TruncateTableStatement {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(TruncateTableStatement::nom_parse(s)
+            .map_err(|err| anyhow!(format!("{err}")))?
+            .1)
+    }
+}
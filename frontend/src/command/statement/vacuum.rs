@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::{alphanumeric1, space1},
+    combinator::opt,
+    sequence::{preceded, tuple},
+};
+
+use crate::{
+    definitions::{table_definition::TableName, NomParsable},
+    errors::DbError,
+};
+
+/// `vacuum [<name>]` rewrites the rows of the table, or of every table when no name is
+/// given, into densely packed pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VacuumStatement(pub Option<TableName>);
+
+impl NomParsable for VacuumStatement {
+    fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
+        let (left, (_, table_name)) =
+            tuple((tag_no_case("vacuum"), opt(preceded(space1, alphanumeric1))))(input.trim())?;
+
+        Ok((
+            left,
+            VacuumStatement(table_name.map(|it| TableName(String::from(it)))),
+        ))
+    }
+}
+
+impl FromStr for VacuumStatement {
+    type Err = DbError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(VacuumStatement::nom_parse(s)
+            .map_err(|err| anyhow!(format!("{err}")))?
+            .1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case("vacuum;", None)]
+    #[case("VACUUM student;", Some("student"))]
+    fn test_parse_vacuum(
+        #[case] statement: &str,
+        #[case] table_name: Option<&str>,
+    ) -> Result<(), DbError> {
+        assert_eq!(
+            VacuumStatement(table_name.map(|it| TableName(String::from(it)))),
+            VacuumStatement::from_str(statement)?
+        );
+        Ok(())
+    }
+}
@@ -1,33 +1,99 @@
 use std::{fmt::Display, str::FromStr};
 
+use anyhow::anyhow;
 use nom::{
     branch::alt,
     bytes::complete::tag_no_case,
-    character::complete::{alphanumeric1, space0, space1},
-    sequence::tuple,
+    character::complete::{alphanumeric1, char, space0, space1, u32, u8},
+    combinator::{all_consuming, map, opt, recognize, verify},
+    sequence::{delimited, preceded, tuple},
 };
 use serde::{Deserialize, Serialize};
 
-use crate::errors::DbError;
+use crate::{
+    command::statement::expression::{parse_expression, Expression},
+    errors::DbError,
+};
 
-use super::NomParsable;
+use super::{ColumnName, NomParsable};
 
+/// Column name, type and the default used for rows that carry no value for it.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-pub struct Column(pub String, pub ColumnType);
+pub struct Column(
+    pub String,
+    pub ColumnType,
+    #[serde(default, skip_serializing_if = "Option::is_none")] pub Option<String>,
+);
+
+// parses the constant expression after `default` and keeps its text, e.g. "default 'new student'"
+fn parse_default(input: &str) -> nom::IResult<&str, String> {
+    preceded(
+        tuple((space1, tag_no_case("default"), space1)),
+        map(
+            verify(recognize(parse_expression), |it: &str| {
+                default_expression(it).is_some()
+            }),
+            String::from,
+        ),
+    )(input)
+}
+
+// functions giving a different value on every call, the default of a column added to a
+// table with rows is evaluated whenever those rows are read, so it has to stay the same
+const VOLATILE_FUNCTIONS: [&str; 4] = ["now", "current_date", "current_time", "current_timestamp"];
+
+// defaults may not refer to columns, a lone word is text like in values lists
+fn default_expression(default: &str) -> Option<Expression> {
+    let (_, expression) = all_consuming(parse_expression)(default).ok()?;
+    match expression {
+        Expression::Column(ColumnName(word)) => Some(Expression::Literal(word)),
+        expression
+            if expression.columns().is_empty()
+                && !expression
+                    .functions()
+                    .iter()
+                    .any(|it| VOLATILE_FUNCTIONS.contains(it)) =>
+        {
+            Some(expression)
+        }
+        _ => None,
+    }
+}
 
 impl NomParsable for Column {
     fn nom_parse(input: &str) -> nom::IResult<&str, Self> {
-        let (left, (_, name, _, col_type)) =
-            tuple((space0, alphanumeric1, space1, ColumnType::nom_parse))(input)?;
+        let (left, (_, name, _, col_type, default)) = tuple((
+            space0,
+            alphanumeric1,
+            space1,
+            ColumnType::nom_parse,
+            opt(parse_default),
+        ))(input)?;
 
-        Ok((left, Column(String::from(name), col_type)))
+        Ok((left, Column(String::from(name), col_type, default)))
     }
 }
 
 impl Column {
+    /// Expression evaluated for rows that carry no value for the column, `None` when
+    /// the column has no default.
+    pub fn default_expression(&self) -> Result<Option<Expression>, DbError> {
+        self.2
+            .as_deref()
+            .map(|default| {
+                default_expression(default).ok_or_else(|| {
+                    DbError::StatementError(anyhow!(
+                        "default {default} of column {} is not a constant expression",
+                        self.0
+                    ))
+                })
+            })
+            .transpose()
+    }
+
     #[allow(dead_code)]
     pub fn new(name: impl Into<String>, column_type: ColumnType) -> Column {
-        Column(name.into(), column_type)
+        Column(name.into(), column_type, None)
     }
 }
 
@@ -35,13 +101,104 @@ impl Column {
 pub enum ColumnType {
     Int,
     Text,
+    /// 64 bit floating point, declared as `real` or `double`.
+    Real,
+    /// `true` or `false`, declared as `bool` or `boolean`.
+    Bool,
+    /// Calendar date, `YYYY-MM-DD`.
+    Date,
+    /// Time of day, `HH:MM:SS[.ffffff]`.
+    Time,
+    /// Date and time of day without time zone, `YYYY-MM-DDTHH:MM:SS[.ffffff]`.
+    Timestamp,
+    /// Exact number with at most `precision` digits, `scale` of them after the point,
+    /// declared as `decimal(precision, scale)` or `numeric(precision, scale)`.
+    Decimal(u8, u8),
+    /// Binary data, declared as `blob` or `bytea` and written as hex like `x'cafe'`.
+    Blob,
+    /// Text of at most the given number of characters, declared as `varchar(n)`.
+    Varchar(u32),
+    /// Text of exactly the given number of characters, padded with spaces,
+    /// declared as `char(n)` or `character(n)`.
+    Char(u32),
+}
+
+impl ColumnType {
+    /// Most digits a decimal can hold, as many as always fit an `i128`.
+    pub const MAX_DECIMAL_PRECISION: u8 = 38;
+    /// Precision of a `decimal` declared without one.
+    pub const DEFAULT_DECIMAL_PRECISION: u8 = 18;
+}
+
+// parses "varchar(20)", "char(2)" or "char", which holds a single character
+fn parse_character(input: &str) -> nom::IResult<&str, ColumnType> {
+    let length = || {
+        verify(
+            delimited(
+                tuple((space0, char('('), space0)),
+                u32,
+                tuple((space0, char(')'))),
+            ),
+            |length| *length > 0,
+        )
+    };
+    alt((
+        map(
+            preceded(tag_no_case("varchar"), length()),
+            ColumnType::Varchar,
+        ),
+        map(
+            preceded(
+                alt((tag_no_case("character"), tag_no_case("char"))),
+                opt(length()),
+            ),
+            |length| ColumnType::Char(length.unwrap_or(1)),
+        ),
+    ))(input)
+}
+
+// parses "decimal", "decimal(10)" or "numeric(10, 2)", the scale defaulting to 0
+fn parse_decimal(input: &str) -> nom::IResult<&str, ColumnType> {
+    let size = delimited(
+        tuple((space0, char('('), space0)),
+        tuple((u8, opt(preceded(tuple((space0, char(','), space0)), u8)))),
+        tuple((space0, char(')'))),
+    );
+    map(
+        verify(
+            preceded(
+                alt((tag_no_case("decimal"), tag_no_case("numeric"))),
+                opt(size),
+            ),
+            |size| match size {
+                Some((precision, scale)) => {
+                    (1..=ColumnType::MAX_DECIMAL_PRECISION).contains(precision)
+                        && scale.unwrap_or(0) <= *precision
+                }
+                None => true,
+            },
+        ),
+        |size| match size {
+            Some((precision, scale)) => ColumnType::Decimal(precision, scale.unwrap_or(0)),
+            None => ColumnType::Decimal(ColumnType::DEFAULT_DECIMAL_PRECISION, 0),
+        },
+    )(input)
 }
 
 impl Display for ColumnType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ColumnType::Int => write!(f, "{}", "int"),
-            ColumnType::Text => write!(f, "{}", "text"),
+            ColumnType::Int => write!(f, "int"),
+            ColumnType::Text => write!(f, "text"),
+            ColumnType::Real => write!(f, "real"),
+            ColumnType::Bool => write!(f, "bool"),
+            ColumnType::Date => write!(f, "date"),
+            ColumnType::Time => write!(f, "time"),
+            ColumnType::Timestamp => write!(f, "timestamp"),
+            ColumnType::Decimal(precision, scale) => write!(f, "decimal({precision},{scale})"),
+            ColumnType::Blob => write!(f, "blob"),
+            ColumnType::Varchar(length) => write!(f, "varchar({length})"),
+            ColumnType::Char(length) => write!(f, "char({length})"),
         }
     }
 }
@@ -55,14 +212,37 @@ impl FromStr for ColumnType {
         match input.as_str() {
             "int" => Ok(Int),
             "text" => Ok(Text),
-            _ => Err(DbError::UnrecognizedColumnType(format!("{s}"))),
+            "real" | "double" => Ok(Real),
+            "bool" | "boolean" => Ok(Bool),
+            "date" => Ok(Date),
+            "time" => Ok(Time),
+            "timestamp" => Ok(Timestamp),
+            "blob" | "bytea" => Ok(Blob),
+            "char" | "character" => Ok(Char(1)),
+            "decimal" | "numeric" => Ok(Decimal(ColumnType::DEFAULT_DECIMAL_PRECISION, 0)),
+            _ => Err(DbError::UnrecognizedColumnType(s.to_string())),
         }
     }
 }
 
 impl NomParsable for ColumnType {
     fn nom_parse(input: &str) -> nom::IResult<&str, ColumnType> {
-        let (left, type_name) = alt((tag_no_case("int"), tag_no_case("text")))(input)?;
+        if let Ok(parsed) = alt((parse_decimal, parse_character))(input) {
+            return Ok(parsed);
+        }
+        let (left, type_name) = alt((
+            tag_no_case("int"),
+            tag_no_case("text"),
+            tag_no_case("real"),
+            tag_no_case("double"),
+            tag_no_case("boolean"),
+            tag_no_case("bool"),
+            tag_no_case("date"),
+            tag_no_case("timestamp"),
+            tag_no_case("time"),
+            tag_no_case("blob"),
+            tag_no_case("bytea"),
+        ))(input)?;
         Ok((left, ColumnType::from_str(type_name).unwrap()))
     }
 }
@@ -20,7 +20,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::errors::DbError;
 
-use super::{column::Column, NomParsable};
+use super::{
+    column::{Column, ColumnType},
+    NomParsable,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct TableName(pub String);
@@ -29,20 +32,48 @@ pub struct TableName(pub String);
 pub struct TableDefinition {
     pub name: TableName,
     pub columns: Vec<Column>,
+    /// Record layouts of earlier schema versions, indexed by version.
+    /// The current columns are the layout of version `schema_history.len()`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schema_history: Vec<SchemaVersion>,
+}
+
+/// Layout records were written with under an earlier schema version.
+/// Each entry is the type a field was stored with and the index of the current column
+/// it belongs to, `None` once that column has been dropped.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+pub struct SchemaVersion(pub Vec<(ColumnType, Option<usize>)>);
+
+impl TableDefinition {
+    /// Schema version records written with the current columns are tagged with.
+    pub fn version(&self) -> usize {
+        self.schema_history.len()
+    }
+
+    /// Layout of the current columns, to be kept in the history before the layout changes.
+    pub fn current_layout(&self) -> SchemaVersion {
+        SchemaVersion(
+            self.columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| (column.1.clone(), Some(index)))
+                .collect(),
+        )
+    }
 }
 
 impl Display for TableDefinition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut table = Table::new();
-        table.add_row(row!["column", "type"]);
+        table.add_row(row!["column", "type", "default"]);
         for col in self.columns.iter() {
-            table.add_row(row![&col.0, &col.1]);
+            table.add_row(row![&col.0, &col.1, col.2.as_deref().unwrap_or("")]);
         }
         std::fmt::Display::fmt(&table, f)
     }
 }
 
-fn parse_columns(columns: &str) -> IResult<&str, Vec<Column>> {
+pub(crate) fn parse_columns(columns: &str) -> IResult<&str, Vec<Column>> {
     let (left, columns) = delimited(
         tag("("),
         separated_list1(tag(","), Column::nom_parse),
@@ -68,6 +99,7 @@ impl NomParsable for TableDefinition {
             TableDefinition {
                 name: TableName(String::from(table_name)),
                 columns,
+                schema_history: Vec::new(),
             },
         ))
     }
@@ -86,6 +118,7 @@ impl FromStr for TableDefinition {
 #[cfg(test)]
 mod test {
 
+    use crate::command::statement::expression::{ArithmeticOperator, Expression};
     use crate::definitions::{
         column::{Column, ColumnType},
         table_definition::TableName,
@@ -95,7 +128,8 @@ mod test {
     use super::TableDefinition;
     #[test]
     fn test_successful() -> Result<(), String> {
-        let create_command = "create table    test ( col1 int, col2 text, col3 int);";
+        let create_command =
+            "create table    test ( col1 int, col2 text default 'n/a', col3 int default -1);";
 
         let result =
             TableDefinition::nom_parse(create_command).map_err(|err| format!("{:?}", err))?;
@@ -105,13 +139,63 @@ mod test {
                 name: TableName(String::from("test")),
                 columns: vec![
                     Column::new("col1", ColumnType::Int),
-                    Column::new("col2", ColumnType::Text),
-                    Column::new("col3", ColumnType::Int)
-                ]
+                    Column(
+                        String::from("col2"),
+                        ColumnType::Text,
+                        Some(String::from("'n/a'"))
+                    ),
+                    Column(
+                        String::from("col3"),
+                        ColumnType::Int,
+                        Some(String::from("-1"))
+                    )
+                ],
+                schema_history: Vec::new(),
             },
             result.1
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_default_expressions() -> Result<(), String> {
+        let create_command =
+            "create table test (col1 int default 1 + 1, col2 int default -(5), col3 text default none);";
+
+        let (_, definition) =
+            TableDefinition::nom_parse(create_command).map_err(|err| format!("{:?}", err))?;
+
+        let defaults = definition
+            .columns
+            .iter()
+            .map(|it| it.default_expression().map_err(|err| err.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            vec![
+                Some(Expression::Arithmetic(
+                    Box::new(Expression::Literal(String::from("1"))),
+                    ArithmeticOperator::Add,
+                    Box::new(Expression::Literal(String::from("1")))
+                )),
+                Some(Expression::Arithmetic(
+                    Box::new(Expression::Literal(String::from("0"))),
+                    ArithmeticOperator::Subtract,
+                    Box::new(Expression::Literal(String::from("5")))
+                )),
+                Some(Expression::Literal(String::from("none"))),
+            ],
+            defaults
+        );
+        assert!(TableDefinition::nom_parse(
+            "create table test (col1 int, col2 int default col1 + 1);"
+        )
+        .is_err());
+        assert!(TableDefinition::nom_parse(
+            "create table test (col1 timestamp default now() - interval '1 day');"
+        )
+        .is_err());
+
+        Ok(())
+    }
 }
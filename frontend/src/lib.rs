@@ -3,7 +3,15 @@ pub mod definitions;
 pub mod errors;
 
 pub use command::meta::DbPath;
-pub use command::statement::{insert::InsertStatement, select::SelectStatement};
+pub use command::statement::{
+    alter::{AlterTableAction, AlterTableStatement},
+    create::{CreateTableAsStatement, CreateTableStatement},
+    drop::DropTableStatement,
+    insert::{InsertSelectStatement, InsertStatement},
+    select::SelectStatement,
+    truncate::TruncateTableStatement,
+    vacuum::VacuumStatement,
+};
 pub use definitions::column::{Column, ColumnType};
 pub use definitions::table_definition::TableDefinition;
 pub use definitions::ColumnName;
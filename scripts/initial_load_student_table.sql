@@ -1,5 +1,3 @@
 create table student(id int, name text, age int);
-insert into student (id, name, age) values(1, john, 21);
-insert into student (id, name, age) values(2, satish, 22);
-insert into student (id, name, age) values(3, alice, 21);
+insert into student (id, name, age) values (1, john, 21), (2, satish, 22), (3, alice, 21);
 select id, name, age from student;
\ No newline at end of file
@@ -4,4 +4,13 @@ use clap::Parser;
 pub(crate) struct Cli {
     #[arg(short, long)]
     pub db_path: std::path::PathBuf,
+    /// Page size in bytes for a new database, a power of two between 4096 and 65536
+    #[arg(long)]
+    pub page_size: Option<usize>,
+    /// Open an existing database without modifying it, rejecting statements other than selects
+    #[arg(long)]
+    pub read_only: bool,
+    /// Check the database for corruption without opening it, printing every problem found
+    #[arg(long)]
+    pub check: bool,
 }
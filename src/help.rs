@@ -7,6 +7,11 @@ pub(super) fn print_help() {
         ".exit".bold().yellow().italic()
     );
 
+    println!(
+        "{} - to check every table's pages and rows as last saved to disk, listing all problems found, or start with --check to check a database which fails to open. \n\n",
+        ".check".bold().yellow().italic()
+    );
+
     println!("{} \n {}\n\n", 
         "use lowercase for db commands.".bold(),
         "This is to simplify case sensitivity for meta names like tables, columns and values like column value".yellow());
@@ -14,21 +19,57 @@ pub(super) fn print_help() {
     println!(
         "{} \neg. {} \nlimitation {}\n\n",
         "create".bold().yellow().italic(),
-        "create table demo (id int, name text);".green(),
-        "current implementation only supports int and text".yellow()
+        "create table if not exists demo (id int, name text default 'unknown'); create table adults as select id, name from student where age > 21;".green(),
+        "supported column types are int, text, varchar(n) and char(n) limited to n characters, real (alias double), bool (alias boolean), decimal(precision, scale) (alias numeric) for exact numbers, blob (alias bytea) written and shown as hex like x'cafe', date, time and timestamp; dates and times are written as iso 8601 text like '2024-02-29T13:45:00' and support interval '1 day', now(), current_date(), current_time() and date_part('year', x)".yellow()
     );
 
     println!(
         "{} \neg. {} \nlimitation {}\n\n",
         "select".bold().yellow().italic(),
-        "select id, name, age from student;".green(),
-        "current implementation requires all column and same order as create table".yellow()
+        "select id, name from student where age > 21 and name != 'john';".green(),
+        "where supports +, -, *, / and =, !=, <, <=, >, >= combined with and, or, not. quote text as 'text'"
+            .yellow()
     );
 
     println!(
         "{} \neg. {} \nlimitation {}\n\n",
         "insert".bold().yellow().italic(),
-        "insert into student (id, name, age) values (10, harry, 21);".green(),
-        "current implementation requires all column and same order as create table".yellow()
+        "insert into student (id, name, age) values (10, harry, 21), (11, ron, 21);".green(),
+        "values fill the listed columns, or every column in order when none are listed; columns left out take their default. all rows are checked before any is written".yellow()
+    );
+
+    println!(
+        "{} \neg. {} \nlimitation {}\n\n",
+        "insert select".bold().yellow().italic(),
+        "insert into alumni (id, name) select id, name from student where age > 21;".green(),
+        "selected columns map to target columns by position".yellow()
+    );
+
+    println!(
+        "{} \neg. {} \nlimitation {}\n\n",
+        "drop".bold().yellow().italic(),
+        "drop table if exists alumni;".green(),
+        "table files are deleted from disk on the next flush".yellow()
+    );
+
+    println!(
+        "{} \neg. {} \nlimitation {}\n\n",
+        "truncate".bold().yellow().italic(),
+        "truncate table student;".green(),
+        "removes all rows but keeps the table definition".yellow()
+    );
+
+    println!(
+        "{} \neg. {} \nlimitation {}\n\n",
+        "vacuum".bold().yellow().italic(),
+        "vacuum student; vacuum;".green(),
+        "rewrites the rows of one or every table into densely packed pages and saves them to disk right away".yellow()
+    );
+
+    println!(
+        "{} \neg. {} \nlimitation {}\n\n",
+        "alter".bold().yellow().italic(),
+        "alter table student add column grade int default 1; alter table student drop column grade; alter table student rename column name to fullname; alter table student rename to pupil;".green(),
+        "adding a column to a table with rows needs a default".yellow()
     );
 }
@@ -1,8 +1,17 @@
 use std::{fs::read_to_string, str::FromStr};
 
+use backend::{vm::ExecutionResult, Database, OpenOptions};
 use clap::Parser;
 use colored::Colorize;
-use frontend::{command::Command, errors::SError};
+use frontend::{
+    command::{
+        meta::MetaCommand,
+        statement::{create::CreateTableStatement, StatementCommand},
+        Command,
+    },
+    errors::SError,
+};
+use prettytable::{Cell, Row, Table};
 use rustyline::{error::ReadlineError, DefaultEditor};
 use tracing::instrument;
 mod cli;
@@ -23,16 +32,34 @@ fn main() -> SError<()> {
     tracing::subscriber::set_global_default(subscriber).expect("failed to install tracing");
 
     let cli = cli::Cli::parse();
+    if cli.check {
+        match Database::check_integrity_at(&cli.db_path) {
+            Ok(problems) => {
+                print_problems(&problems);
+                std::process::exit(if problems.is_empty() { 0 } else { 1 });
+            }
+            Err(err) => {
+                eprintln!("Failed to check database: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
     let db_exists = cli.db_path.exists();
-    if !db_exists {
-        load_sample_data();
+    let mut options = OpenOptions::new().read_only(cli.read_only);
+    if let Some(page_size) = cli.page_size {
+        options = options.page_size(page_size);
     }
+    let database = match options.open(cli.db_path) {
+        Ok(database) => database,
+        Err(err) => {
+            eprintln!("Failed to open database: {err}");
+            std::process::exit(1);
+        }
+    };
 
-    backend::vm::initialize_db(frontend::DbPath(cli.db_path)).expect("Failed to initialize db");
-
-    // add code to save loaded data.
     if !db_exists {
-        backend::vm::persist_to_db().expect("Failed to persist to disk");
+        load_sample_data(&database);
+        database.flush().expect("Failed to persist to disk");
     }
 
     let mut rl = DefaultEditor::new().expect("Failed to open readline editor");
@@ -43,7 +70,7 @@ fn main() -> SError<()> {
         match readline {
             Ok(line) => {
                 let _ = rl.add_history_entry(line.as_str());
-                execute_command(&line);
+                execute_command(&database, &line);
             }
             Err(ReadlineError::Interrupted) => {
                 println!("CTRL-C");
@@ -64,35 +91,76 @@ fn main() -> SError<()> {
     Ok(())
 }
 
-#[instrument(name = "test")]
-fn execute_command(prompt: &str) {
+#[instrument(name = "test", skip(database))]
+fn execute_command(database: &Database, prompt: &str) {
     if prompt == "help" {
         help::print_help();
         return;
     }
-    let command_result = Command::from_str(&prompt);
+    let command_result = Command::from_str(prompt);
     let Ok(command) = command_result else {
         println!("{:?}", command_result.err().unwrap());
         return;
     };
 
     match command {
-        Command::Meta(meta_command) => match meta_command {
-            _ => {
-                backend::vm::persist_to_db().expect("failed to save to db");
-                std::process::exit(0);
-            }
+        Command::Meta(MetaCommand::Check) => match database.check_integrity() {
+            Ok(problems) => print_problems(&problems),
+            Err(err) => println!("Failed with {err:?}"),
         },
+        Command::Meta(_meta_command) => {
+            if !database.is_read_only() {
+                database.flush().expect("failed to save to db");
+            }
+            std::process::exit(0);
+        }
         Command::Statement(statement) => {
-            if let Err(err) = backend::vm::execute(statement) {
-                println!("Failed with {err:?}");
+            if let StatementCommand::Create(CreateTableStatement(definition, _)) = &statement {
+                println!("Table def");
+                println!("{definition}");
+            }
+            match database.execute(statement) {
+                Ok(ExecutionResult::SelectResult(rows)) => {
+                    if let Err(err) = print_rows(rows) {
+                        println!("Failed with {err:?}");
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => println!("Failed with {err:?}"),
             }
         }
     }
 }
 
-#[instrument]
-fn load_sample_data() {
+fn print_problems(problems: &[backend::IntegrityProblem]) {
+    if problems.is_empty() {
+        println!("ok");
+    }
+    for problem in problems {
+        println!("{problem}");
+    }
+}
+
+fn print_rows(rows: backend::Rows) -> backend::BEResult<()> {
+    let mut table = Table::new();
+    table.add_row(Row::new(
+        rows.columns()
+            .iter()
+            .map(|it| Cell::new(&it.0))
+            .collect::<Vec<_>>(),
+    ));
+    for row in rows {
+        table.add_row(Row::new(
+            row?.into_iter().map(|it| Cell::new(&it.value())).collect(),
+        ));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+#[instrument(skip(database))]
+fn load_sample_data(database: &Database) {
     tracing::info!("");
     for command in read_to_string("scripts/initial_load_student_table.sql")
         .unwrap()
@@ -105,7 +173,9 @@ fn load_sample_data() {
         match command {
             Command::Meta(_) => panic!("only statement command are allowed in pre-load"),
             Command::Statement(statement) => {
-                backend::vm::execute(statement).expect("Failed to execute statement")
+                database
+                    .execute(statement)
+                    .expect("Failed to execute statement");
             }
         };
     }